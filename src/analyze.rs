@@ -0,0 +1,169 @@
+//! Side-effect-free inspection of the loaded modules' GOTs.
+//!
+//! Nothing in this module modifies the process: it never changes memory
+//! protections, never writes to a GOT, and never registers hooks. It only
+//! reads memory (and, for [`SearchStrategy::OnDiskRelocation`], module
+//! files), so it's safe to use from analysis tools that must leave their
+//! target untouched.
+
+use std::ptr;
+
+use crate::elf_image::ElfImage;
+use crate::error;
+use crate::module_info::{self, ModuleInfo};
+use crate::relocations::RelocationSource;
+use crate::search_strategy::SearchStrategy;
+use crate::GotHook;
+
+/// A GOT entry, and the value it currently holds.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GotEntry {
+    /// The name of the function the GOT entry is relocated to.
+    pub function_name: String,
+
+    /// The address of the GOT entry.
+    pub address: u64,
+
+    /// The function the GOT entry currently points to.
+    pub value: u64,
+
+    /// The type of the relocation the GOT entry was located through, if any.
+    pub relocation_type: Option<u32>,
+
+    /// The index of that relocation within its relocation table.
+    pub relocation_index: Option<usize>,
+
+    /// The raw `r_offset` of that relocation.
+    pub relocation_offset: Option<u64>,
+}
+
+/// Lists the modules loaded into the current process, in address order.
+pub fn modules() -> error::Result<Vec<ModuleInfo>> {
+    module_info::loaded_modules()
+}
+
+/// Returns the address the main executable is loaded at, to inspect its GOT
+/// separately from the libraries'.
+pub fn main_executable_base() -> error::Result<u64> {
+    GotHook::find_main_executable_base()
+}
+
+/// Locates `function_name`'s GOT entry in the ELF image loaded at
+/// `base_address`, using `strategy`.
+pub fn resolve(
+    base_address: u64,
+    function_name: &str,
+    strategy: SearchStrategy,
+) -> error::Result<GotEntry> {
+    let target = GotHook::resolve_got_entry(base_address, function_name, strategy)?;
+
+    Ok(GotEntry {
+        function_name: String::from(function_name),
+        address: target.got_entry,
+        value: read_got_entry(target.got_entry),
+        relocation_type: target.relocation.map(|r| r.reloc_type),
+        relocation_index: target.relocation.map(|r| r.index),
+        relocation_offset: target.relocation.map(|r| r.offset),
+    })
+}
+
+/// Lists the GOT entries of the ELF image loaded at `base_address`: those of
+/// its jump slot and glob-dat relocations.
+pub fn inventory(base_address: u64) -> error::Result<Vec<GotEntry>> {
+    // Find the ELF in memory.
+    let image = ElfImage::find(base_address)?;
+    let elf_machine = image.machine;
+
+    let mut entries = Vec::new();
+    let mut relocations = image.relocations()?;
+    while let Some(relocation) = relocations.next() {
+        // Skip relocations that don't point a GOT entry at a function.
+        let is_got_entry = if RelocationSource::Plt == relocation.source {
+            elf_machine.jump_slot_type() == relocation.reloc_type
+        } else {
+            elf_machine.glob_dat_type() == relocation.reloc_type
+        };
+        if !is_got_entry {
+            continue;
+        }
+        let Some(symbol_name) = relocations.symbol_name(&relocation)? else {
+            continue;
+        };
+
+        entries.push(GotEntry {
+            function_name: String::from(symbol_name),
+            address: relocation.got_address,
+            value: read_got_entry(relocation.got_address),
+            relocation_type: Some(relocation.reloc_type),
+            relocation_index: Some(relocation.index),
+            relocation_offset: Some(relocation.offset),
+        });
+    }
+
+    Ok(entries)
+}
+
+/// Returns the address and current value of every GOT entry listed by
+/// [`inventory`], to compare the GOT's state at different times.
+pub fn snapshot(base_address: u64) -> error::Result<Vec<(u64, u64)>> {
+    Ok(inventory(base_address)?
+        .into_iter()
+        .map(|e| (e.address, e.value))
+        .collect())
+}
+
+fn read_got_entry(address: u64) -> u64 {
+    unsafe { ptr::read_volatile(address as *const u64) }
+}
+
+#[cfg(test)]
+mod tests {
+    use object::elf;
+
+    use super::*;
+    use crate::protection::Protection;
+    use crate::test_elf::{self, ElfBuilder};
+
+    #[test]
+    fn leaves_the_analyzed_image_untouched() {
+        let _lock = test_elf::lock_loaded_modules();
+
+        let image = ElfBuilder::new(elf::EM_AARCH64)
+            .sections()
+            .import("read")
+            .import("write")
+            .import_data("environ")
+            .build()
+            .map_file("libgothook-analyzed.so");
+        image.write_got_entry("read", 0x1000);
+        image.write_got_entry("write", 0x2000);
+        Protection::ReadOnly.apply(image.got_entry("read")).unwrap();
+
+        let before = snapshot(image.base_address()).unwrap();
+
+        // Run every analysis, whether or not it finds anything.
+        assert!(modules()
+            .unwrap()
+            .iter()
+            .any(|m| m.base_address == image.base_address()));
+        main_executable_base().unwrap();
+        for function_name in ["read", "write", "environ"] {
+            for strategy in [
+                SearchStrategy::PltRelocation,
+                SearchStrategy::GlobDatRelocation,
+                SearchStrategy::OnDiskRelocation,
+                SearchStrategy::GotScan,
+            ] {
+                let _ = resolve(image.base_address(), function_name, strategy);
+            }
+        }
+        assert_eq!(3, inventory(image.base_address()).unwrap().len());
+
+        assert_eq!(before, snapshot(image.base_address()).unwrap());
+        assert_eq!(
+            Protection::ReadOnly,
+            Protection::of_address(image.got_entry("read")).unwrap()
+        );
+        assert!(!crate::is_hooked(image.path().to_str().unwrap(), "read").unwrap());
+    }
+}
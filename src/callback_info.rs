@@ -0,0 +1,16 @@
+/// The dynamic linker's symbolic information for a hook's callback, as
+/// reported by `dladdr`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CallbackInfo {
+    /// The path of the module containing the callback (`dli_fname`).
+    pub module_name: String,
+
+    /// The address the module is loaded at (`dli_fbase`).
+    pub module_base: u64,
+
+    /// The name of the symbol nearest to the callback (`dli_sname`), if any.
+    pub symbol_name: Option<String>,
+
+    /// The address of that symbol (`dli_saddr`).
+    pub symbol_address: u64,
+}
@@ -0,0 +1,121 @@
+use std::mem;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+use crate::error;
+use crate::protection::{self, Protection};
+
+static DEFER_REPROTECTS: AtomicBool = AtomicBool::new(false);
+
+lazy_static::lazy_static! {
+    static ref PENDING_REPROTECTS: Mutex<Vec<(u64, Protection)>> = Mutex::new(Vec::new());
+}
+
+/// Sets whether unhooking defers restoring the original protection of the GOT
+/// entry's page until [`flush_pending_reprotects`] is called, so that it (and
+/// dropping a hook) only restores the GOT entry itself.
+///
+/// Until the flush, the page stays writable: anyone can overwrite the GOT
+/// entries on it, including those RELRO should have made read-only.
+pub fn defer_reprotects(defer: bool) {
+    DEFER_REPROTECTS.store(defer, Ordering::Relaxed);
+}
+
+/// Restores the original protection of the pages deferred by
+/// [`defer_reprotects`].
+///
+/// Every page is restored, even when restoring a previous one fails, in which
+/// case the first error is returned.
+pub fn flush_pending_reprotects() -> error::Result<()> {
+    let pending_reprotects = mem::take(&mut *PENDING_REPROTECTS.lock().unwrap());
+
+    let mut result = Ok(());
+    for (address, protection) in pending_reprotects {
+        let reprotect_result = protection.apply(address);
+        if result.is_ok() {
+            result = reprotect_result;
+        }
+    }
+
+    result
+}
+
+/// Restores `protection` to the page containing `address`, now or when the
+/// pending reprotects are flushed.
+pub(crate) fn reprotect(address: u64, protection: Protection) -> error::Result<()> {
+    if DEFER_REPROTECTS.load(Ordering::Relaxed) {
+        PENDING_REPROTECTS
+            .lock()
+            .unwrap()
+            .push((address, protection));
+        return Ok(());
+    }
+
+    protection.apply(address)
+}
+
+/// Cancels the pending reprotects of the page containing `address`, e.g.
+/// because it's hooked again, returning the protection it was to be restored
+/// to.
+pub(crate) fn cancel(address: u64) -> Option<Protection> {
+    let page = protection::page_of(address);
+    let mut pending_reprotects = PENDING_REPROTECTS.lock().unwrap();
+
+    let original_protection = pending_reprotects
+        .iter()
+        .find(|&&(a, _)| page == protection::page_of(a))
+        .map(|&(_, p)| p);
+    pending_reprotects.retain(|&(a, _)| page != protection::page_of(a));
+
+    original_protection
+}
+
+#[cfg(test)]
+mod tests {
+    use object::elf;
+
+    use super::*;
+    use crate::test_elf::{self, ElfBuilder};
+    use crate::GotHook;
+
+    #[test]
+    fn restores_the_protection_only_when_flushed() {
+        let _lock = test_elf::lock_reprotects();
+
+        let image = ElfBuilder::new(elf::EM_AARCH64)
+            .import("read")
+            .build()
+            .map();
+        let got_entry = image.got_entry("read");
+        // As after RELRO.
+        Protection::ReadOnly.apply(got_entry).unwrap();
+
+        defer_reprotects(true);
+        let hook =
+            GotHook::new_with_base(image.base_address(), "read", 0x1000 as *const ()).unwrap();
+        hook.unhook().unwrap();
+        assert_eq!(
+            Protection::ReadWrite,
+            Protection::of_address(got_entry).unwrap()
+        );
+
+        // Hooking the page again cancels its pending reprotect, until the new
+        // hook is removed.
+        let hook =
+            GotHook::new_with_base(image.base_address(), "read", 0x1000 as *const ()).unwrap();
+        assert!(hook.state().page_protection_modified);
+        flush_pending_reprotects().unwrap();
+        assert_eq!(
+            Protection::ReadWrite,
+            Protection::of_address(got_entry).unwrap()
+        );
+        hook.unhook().unwrap();
+        defer_reprotects(false);
+
+        flush_pending_reprotects().unwrap();
+        assert_eq!(
+            Protection::ReadOnly,
+            Protection::of_address(got_entry).unwrap()
+        );
+    }
+}
@@ -0,0 +1,85 @@
+use object::elf;
+use object::read::elf::{Dyn, FileHeader};
+
+// `DT_RELR` isn't known to the `object` crate yet.
+pub(crate) const DT_RELRSZ: u32 = 35;
+pub(crate) const DT_RELR: u32 = 36;
+
+// Dynamic tags whose value is the address of a table in the ELF.
+pub(crate) const ADDRESS_TAGS: [u32; 14] = [
+    elf::DT_HASH,
+    elf::DT_GNU_HASH,
+    elf::DT_STRTAB,
+    elf::DT_SYMTAB,
+    elf::DT_RELA,
+    elf::DT_REL,
+    elf::DT_JMPREL,
+    DT_RELR,
+    elf::DT_PLTGOT,
+    elf::DT_VERSYM,
+    elf::DT_VERNEED,
+    elf::DT_VERDEF,
+    elf::DT_INIT_ARRAY,
+    elf::DT_FINI_ARRAY,
+];
+
+pub(crate) fn dynamic_entries<Elf: FileHeader>(
+    dynamic_segment: &[Elf::Dyn],
+    endian: Elf::Endian,
+) -> impl Iterator<Item = &Elf::Dyn> {
+    // Entries following `DT_NULL` are padding, not part of the dynamic segment.
+    dynamic_segment
+        .iter()
+        .take_while(move |&e| e.tag32(endian) != Some(elf::DT_NULL))
+}
+
+pub(crate) fn find_dynamic_value<Elf: FileHeader>(
+    dynamic_segment: &[Elf::Dyn],
+    endian: Elf::Endian,
+    tag: u32,
+) -> Option<u64> {
+    dynamic_entries::<Elf>(dynamic_segment, endian)
+        .find(|&e| e.tag32(endian).map(|t| tag == t).unwrap_or(false))
+        .map(|e| e.d_val(endian).into())
+}
+
+pub(crate) fn find_dynamic_table<Elf: FileHeader>(
+    data: &[u8],
+    dynamic_segment: &[Elf::Dyn],
+    endian: Elf::Endian,
+    address_tag: u32,
+    size_tag: u32,
+) -> Option<(u64, u64)> {
+    let address = find_dynamic_value::<Elf>(dynamic_segment, endian, address_tag)?;
+    let size = find_dynamic_table_size::<Elf>(data, dynamic_segment, endian, address, size_tag);
+
+    Some((address, size))
+}
+
+pub(crate) fn find_dynamic_table_size<Elf: FileHeader>(
+    data: &[u8],
+    dynamic_segment: &[Elf::Dyn],
+    endian: Elf::Endian,
+    address: u64,
+    size_tag: u32,
+) -> u64 {
+    if let Some(size) = find_dynamic_value::<Elf>(dynamic_segment, endian, size_tag) {
+        return size;
+    }
+
+    // Minimal dynamic segments (e.g. in static binaries with a handful of
+    // imports) may omit the size tags. Bound the table by the closest
+    // table that follows it, or by the end of the ELF if there is none.
+    let end = dynamic_entries::<Elf>(dynamic_segment, endian)
+        .filter(|&e| {
+            e.tag32(endian)
+                .map(|t| ADDRESS_TAGS.contains(&t))
+                .unwrap_or(false)
+        })
+        .map(|e| e.d_val(endian).into())
+        .filter(|&a| a > address)
+        .min()
+        .unwrap_or(data.len() as u64);
+
+    end.saturating_sub(address)
+}
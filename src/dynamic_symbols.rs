@@ -0,0 +1,181 @@
+use std::mem;
+use std::str;
+
+use object::elf;
+use object::read::elf::{FileHeader, GnuHashTable, HashTable, Sym};
+use object::read::StringTable;
+use object::ReadRef;
+
+use crate::dynamic_segment;
+use crate::error;
+
+pub(crate) struct DynamicSymbols<'a, Elf: FileHeader> {
+    data: &'a [u8],
+    endian: Elf::Endian,
+    table_address: u64,
+    count: u32,
+    strings: StringTable<'a, &'a [u8]>,
+}
+
+impl<'a, Elf: FileHeader> DynamicSymbols<'a, Elf> {
+    pub(crate) fn new(
+        data: &'a [u8],
+        dynamic_segment: &'a [Elf::Dyn],
+        endian: Elf::Endian,
+    ) -> error::Result<Self> {
+        // Find the dynamic symbol table address.
+        let table_address =
+            dynamic_segment::find_dynamic_value::<Elf>(dynamic_segment, endian, elf::DT_SYMTAB)
+                .ok_or(error::Error::ElfHasNoDynamicSymbolTable)?;
+
+        // Find the number of dynamic symbols, to validate symbol indices.
+        let count = Self::find_count(data, dynamic_segment, endian)?;
+
+        // Read the dynamic string table.
+        let strings_address =
+            dynamic_segment::find_dynamic_value::<Elf>(dynamic_segment, endian, elf::DT_STRTAB)
+                .ok_or(error::Error::ElfHasNoDynamicStringTable)?;
+        let strings_size = dynamic_segment::find_dynamic_table_size::<Elf>(
+            data,
+            dynamic_segment,
+            endian,
+            strings_address,
+            elf::DT_STRSZ,
+        );
+        let strings = StringTable::new(data, strings_address, strings_address + strings_size);
+
+        Ok(Self {
+            data,
+            endian,
+            table_address,
+            count,
+            strings,
+        })
+    }
+
+    fn find_count(
+        data: &[u8],
+        dynamic_segment: &[Elf::Dyn],
+        endian: Elf::Endian,
+    ) -> error::Result<u32> {
+        // The dynamic symbol table's size isn't recorded anywhere, and it isn't
+        // necessarily followed by the dynamic string table. Only the hash
+        // tables reliably tell how many symbols it has.
+        if let Some(address) =
+            dynamic_segment::find_dynamic_value::<Elf>(dynamic_segment, endian, elf::DT_HASH)
+        {
+            let hash_table = data
+                .get(address as usize..)
+                .and_then(|d| HashTable::<Elf>::parse(endian, d).ok())
+                .ok_or(error::Error::ReadElfHashTable)?;
+
+            return Ok(hash_table.symbol_table_length());
+        }
+
+        if let Some(address) =
+            dynamic_segment::find_dynamic_value::<Elf>(dynamic_segment, endian, elf::DT_GNU_HASH)
+        {
+            let hash_table = data
+                .get(address as usize..)
+                .and_then(|d| GnuHashTable::<Elf>::parse(endian, d).ok())
+                .ok_or(error::Error::ReadElfHashTable)?;
+
+            // A GNU hash table without any hashed symbols only covers the
+            // symbols preceding its symbol base.
+            return Ok(hash_table
+                .symbol_table_length(endian)
+                .unwrap_or(hash_table.symbol_base()));
+        }
+
+        Err(error::Error::CannotDetermineSymbolCount)
+    }
+
+    pub(crate) fn symbol(&self, index: u32) -> error::Result<&'a Elf::Sym> {
+        if index >= self.count {
+            return Err(error::Error::SymbolIndexOutOfBounds(index));
+        }
+
+        self.data
+            .read_at(self.table_address + (index as u64 * mem::size_of::<Elf::Sym>() as u64))
+            .map_err(|_| error::Error::ReadElfSymbol)
+    }
+
+    pub(crate) fn symbol_name(&self, symbol: &Elf::Sym) -> error::Result<&'a str> {
+        str::from_utf8(
+            symbol
+                .name(self.endian, self.strings)
+                .map_err(error::Error::FindElfSymbolName)?,
+        )
+        .map_err(error::Error::NonUtf8ElfSymbolName)
+    }
+
+    pub(crate) fn find(&self, name: &str) -> error::Result<Option<&'a Elf::Sym>> {
+        // Symbol 0 is the undefined symbol, which has no name.
+        for index in 1..self.count {
+            let symbol = self.symbol(index)?;
+            if self.symbol_name(symbol)? == name {
+                return Ok(Some(symbol));
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use object::elf::FileHeader64;
+    use object::endian::Endianness;
+
+    use super::*;
+    use crate::test_elf::{ElfBuilder, HashTable, TestElf};
+
+    type Elf = FileHeader64<Endianness>;
+
+    fn build(hash_table: HashTable) -> TestElf {
+        // The string table doesn't follow the symbol table.
+        ElfBuilder::new(elf::EM_AARCH64)
+            .hash_table(hash_table)
+            .import("read")
+            .import("write")
+            .import_data("environ")
+            .build()
+    }
+
+    fn symbols(image: &TestElf) -> error::Result<DynamicSymbols<'_, Elf>> {
+        DynamicSymbols::<Elf>::new(
+            &image.bytes,
+            image.dynamic_segment::<Elf>(),
+            Endianness::Little,
+        )
+    }
+
+    #[test]
+    fn counts_symbols_with_either_hash_table() {
+        for hash_table in [HashTable::Sysv, HashTable::Gnu] {
+            let image = build(hash_table);
+            let symbols = symbols(&image).unwrap();
+
+            // The null symbol, and the three imports.
+            assert_eq!(4, symbols.count);
+            let names: Vec<&str> = (1..4)
+                .map(|i| symbols.symbol_name(symbols.symbol(i).unwrap()).unwrap())
+                .collect();
+            assert_eq!(names, ["read", "write", "environ"]);
+            assert!(matches!(
+                symbols.symbol(4),
+                Err(error::Error::SymbolIndexOutOfBounds(4))
+            ));
+        }
+    }
+
+    #[test]
+    fn requires_a_hash_table_to_count_symbols() {
+        let image = build(HashTable::None);
+
+        assert!(matches!(
+            symbols(&image),
+            Err(error::Error::CannotDetermineSymbolCount)
+        ));
+    }
+}
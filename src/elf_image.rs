@@ -0,0 +1,159 @@
+use std::mem;
+use std::slice;
+
+use object::elf::{self, Dyn64, FileHeader64, ProgramHeader64};
+use object::endian::Endianness;
+use object::read::elf::{FileHeader, ProgramHeader};
+use object::ReadRef;
+
+use crate::error;
+use crate::machine::Machine;
+use crate::relocations::Relocations;
+
+/// An ELF image loaded in memory, whose architecture can be hooked.
+///
+/// Only the ELF header is parsed up front: the program headers and the
+/// dynamic segment are read as they're needed.
+pub(crate) struct ElfImage {
+    pub(crate) base_address: u64,
+    pub(crate) data: &'static [u8],
+    pub(crate) header: &'static FileHeader64<Endianness>,
+    pub(crate) endian: Endianness,
+    pub(crate) machine: Machine,
+}
+
+impl ElfImage {
+    pub(crate) fn find(base_address: u64) -> error::Result<Self> {
+        // Find the ELF in memory.
+        let data = find_in_memory(base_address)?;
+
+        // Parse the ELF's header.
+        let header: &FileHeader64<Endianness> =
+            FileHeader64::parse(data).map_err(error::Error::ParseElfHeader)?;
+
+        // Get the ELF's endianness.
+        let endian = header.endian().map_err(error::Error::GetElfEndianness)?;
+
+        // Make sure we know how to hook the ELF's architecture.
+        let machine = Machine::from_elf_header(header, endian)?;
+
+        Ok(Self {
+            base_address,
+            data,
+            header,
+            endian,
+            machine,
+        })
+    }
+
+    pub(crate) fn segments(&self) -> error::Result<&'static [ProgramHeader64<Endianness>]> {
+        read_segments(self.data, self.header, self.endian)
+    }
+
+    pub(crate) fn dynamic_segment(&self) -> error::Result<&'static [Dyn64<Endianness>]> {
+        // Find the dynamic segment program header. There must be exactly one,
+        // the dynamic linker wouldn't tell which of several is authoritative.
+        let mut dynamic_program_headers = self
+            .segments()?
+            .iter()
+            .filter(|&s| elf::PT_DYNAMIC == s.p_type(self.endian));
+        let program_header = dynamic_program_headers
+            .next()
+            .ok_or(error::Error::ElfHasNoDynamicSegment)?;
+        let extra_dynamic_segments = dynamic_program_headers.count();
+        if 0 != extra_dynamic_segments {
+            return Err(error::Error::MultipleDynamicSegments(
+                extra_dynamic_segments + 1,
+            ));
+        }
+
+        // Read the dynamic segment.
+        self.data
+            .read_slice_at(
+                program_header.p_vaddr(self.endian),
+                program_header.p_memsz(self.endian) as usize / mem::size_of::<Dyn64<Endianness>>(),
+            )
+            .map_err(|_| error::Error::ReadElfDynamicSegment)
+    }
+
+    pub(crate) fn relocations(
+        &self,
+    ) -> error::Result<Relocations<'static, FileHeader64<Endianness>>> {
+        Relocations::new(
+            self.base_address,
+            self.machine,
+            self.data,
+            self.dynamic_segment()?,
+            self.endian,
+        )
+    }
+}
+
+fn find_in_memory(base_address: u64) -> error::Result<&'static [u8]> {
+    // Parse the ELF's header.
+    let header_data = unsafe {
+        slice::from_raw_parts(
+            base_address as *const u8,
+            mem::size_of::<FileHeader64<Endianness>>(),
+        )
+    };
+    let header: &FileHeader64<Endianness> =
+        FileHeader64::parse(header_data).map_err(error::Error::ParseElfHeader)?;
+    let endian = header.endian().map_err(error::Error::GetElfEndianness)?;
+
+    // Read the ELF's program headers.
+    let program_headers_end = header.e_phoff(endian)
+        + header.e_phnum(endian) as u64 * mem::size_of::<ProgramHeader64<Endianness>>() as u64;
+    let program_headers_data =
+        unsafe { slice::from_raw_parts(base_address as *const u8, program_headers_end as usize) };
+
+    // The ELF's memory range ends with its last loadable segment. This
+    // doesn't rely on the process's maps, so it also works for ELF images
+    // that aren't backed by a file.
+    let loadable_segments = read_segments(program_headers_data, header, endian)?
+        .iter()
+        .filter(|s| elf::PT_LOAD == s.p_type(endian));
+    let link_address = loadable_segments
+        .clone()
+        .map(|s| s.p_vaddr(endian))
+        .min()
+        .ok_or(error::Error::ElfHasNoLoadableSegments)?;
+
+    // Addresses in the ELF are treated as offsets from its base address,
+    // which only holds for ELFs linked at address 0 (i.e. not for
+    // `ET_EXEC` or prelinked ELFs).
+    if 0 != link_address {
+        return Err(error::Error::UnsupportedElfLinkAddress(link_address));
+    }
+
+    let size = loadable_segments
+        .map(|s| s.p_vaddr(endian) + s.p_memsz(endian))
+        .max()
+        .ok_or(error::Error::ElfHasNoLoadableSegments)?;
+
+    // Create a slice that contains the ELF in-memory.
+    Ok(unsafe { slice::from_raw_parts(base_address as *const u8, size as usize) })
+}
+
+fn read_segments<'a>(
+    data: &'a [u8],
+    header: &FileHeader64<Endianness>,
+    endian: Endianness,
+) -> error::Result<&'a [ProgramHeader64<Endianness>]> {
+    // Get the ELF's program headers offset.
+    let program_headers_offset = header.e_phoff(endian);
+    if 0 == program_headers_offset {
+        return Err(error::Error::ElfHasNoProgramHeaders);
+    }
+
+    // Get the number of program headers in the ELF.
+    let program_headers_number = header
+        .phnum(endian, data)
+        .map_err(error::Error::GetElfProgramHeadersNumber)?;
+    if 0 == program_headers_number {
+        return Err(error::Error::ElfHasNoProgramHeaders);
+    }
+
+    data.read_slice_at(program_headers_offset, program_headers_number)
+        .map_err(|_| error::Error::ReadElfProgramHeaders)
+}
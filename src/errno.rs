@@ -0,0 +1,12 @@
+use nix::errno::Errno;
+
+pub(crate) fn set_errno(errno: Errno) {
+    #[cfg(target_os = "android")]
+    let errno_location = unsafe { libc::__errno() };
+    #[cfg(not(target_os = "android"))]
+    let errno_location = unsafe { libc::__errno_location() };
+
+    unsafe {
+        *errno_location = errno as libc::c_int;
+    }
+}
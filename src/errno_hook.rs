@@ -0,0 +1,247 @@
+use std::ffi::{c_char, c_int, c_void};
+use std::mem;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+
+use libc::{mode_t, size_t, ssize_t};
+use nix::errno::Errno;
+
+use crate::errno;
+use crate::error;
+use crate::libc_functions::{
+    get_original_function, OpenFunction, OpenatFunction, SendFunction, WriteFunction,
+};
+use crate::telemetry;
+use crate::GotHook;
+
+pub type OpenCallback = fn(OpenFunction, *const c_char, c_int, mode_t) -> Result<c_int, Errno>;
+pub type OpenatCallback =
+    fn(OpenatFunction, c_int, *const c_char, c_int, mode_t) -> Result<c_int, Errno>;
+pub type WriteCallback = fn(WriteFunction, c_int, *const c_void, size_t) -> Result<ssize_t, Errno>;
+pub type SendCallback =
+    fn(SendFunction, c_int, *const c_void, size_t, c_int) -> Result<ssize_t, Errno>;
+
+/// A callback for one of the curated prototypes that reports failures as an
+/// [`Errno`] instead of following the libc convention.
+///
+/// The callback is passed the original function first, to forward the call
+/// to: calling the function itself (e.g. `libc::open`) from the main
+/// executable would call the hook again.
+///
+/// When the callback returns `Err(errno)`, the hooked function sets `errno`
+/// and returns -1.
+#[derive(Clone, Copy, Debug)]
+pub enum ErrnoCallback {
+    Open(OpenCallback),
+    Openat(OpenatCallback),
+    Write(WriteCallback),
+    Send(SendCallback),
+}
+
+static OPEN_CALLBACK: AtomicU64 = AtomicU64::new(0);
+static OPENAT_CALLBACK: AtomicU64 = AtomicU64::new(0);
+static WRITE_CALLBACK: AtomicU64 = AtomicU64::new(0);
+static SEND_CALLBACK: AtomicU64 = AtomicU64::new(0);
+
+static ORIGINAL_OPEN: AtomicU64 = AtomicU64::new(0);
+static ORIGINAL_OPENAT: AtomicU64 = AtomicU64::new(0);
+static ORIGINAL_WRITE: AtomicU64 = AtomicU64::new(0);
+static ORIGINAL_SEND: AtomicU64 = AtomicU64::new(0);
+
+// Hooking a hooked function again would make its trampoline its own original
+// function.
+static OPEN_HOOKED: AtomicBool = AtomicBool::new(false);
+static OPENAT_HOOKED: AtomicBool = AtomicBool::new(false);
+static WRITE_HOOKED: AtomicBool = AtomicBool::new(false);
+static SEND_HOOKED: AtomicBool = AtomicBool::new(false);
+
+pub(crate) fn install(callback: ErrnoCallback) -> error::Result<GotHook> {
+    let (function_name, trampoline, callback_slot, original_function, hooked, callback) =
+        match callback {
+            ErrnoCallback::Open(c) => (
+                "open",
+                open_trampoline as *const (),
+                &OPEN_CALLBACK,
+                &ORIGINAL_OPEN,
+                &OPEN_HOOKED,
+                c as *const (),
+            ),
+            ErrnoCallback::Openat(c) => (
+                "openat",
+                openat_trampoline as *const (),
+                &OPENAT_CALLBACK,
+                &ORIGINAL_OPENAT,
+                &OPENAT_HOOKED,
+                c as *const (),
+            ),
+            ErrnoCallback::Write(c) => (
+                "write",
+                write_trampoline as *const (),
+                &WRITE_CALLBACK,
+                &ORIGINAL_WRITE,
+                &WRITE_HOOKED,
+                c as *const (),
+            ),
+            ErrnoCallback::Send(c) => (
+                "send",
+                send_trampoline as *const (),
+                &SEND_CALLBACK,
+                &ORIGINAL_SEND,
+                &SEND_HOOKED,
+                c as *const (),
+            ),
+        };
+
+    if hooked
+        .compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire)
+        .is_err()
+    {
+        return Err(error::Error::CallbackAlreadyInstalled(String::from(
+            function_name,
+        )));
+    }
+
+    // The trampoline must find the callback as soon as it's hooked in.
+    callback_slot.store(callback as u64, Ordering::Release);
+
+    let mut hook = match GotHook::new_in_main_executable(function_name, trampoline) {
+        Ok(hook) => hook,
+        Err(e) => {
+            hooked.store(false, Ordering::Release);
+            return Err(e);
+        }
+    };
+    hook.mirror_original_function(original_function);
+    hook.clear_on_restore(hooked);
+
+    Ok(hook)
+}
+
+fn get_callback(callback_slot: &AtomicU64) -> *const () {
+    callback_slot.load(Ordering::Acquire) as *const ()
+}
+
+fn into_libc_result<T: From<i8>>(result: Result<T, Errno>) -> T {
+    match result {
+        Ok(value) => value,
+        Err(e) => {
+            errno::set_errno(e);
+            T::from(-1)
+        }
+    }
+}
+
+extern "C" fn open_trampoline(path: *const c_char, flags: c_int, mode: mode_t) -> c_int {
+    telemetry::call_intercepted();
+    let callback =
+        unsafe { mem::transmute::<*const (), OpenCallback>(get_callback(&OPEN_CALLBACK)) };
+    let original_open = unsafe {
+        mem::transmute::<*const (), OpenFunction>(get_original_function("open", &ORIGINAL_OPEN))
+    };
+    into_libc_result(callback(original_open, path, flags, mode))
+}
+
+extern "C" fn openat_trampoline(
+    dirfd: c_int,
+    path: *const c_char,
+    flags: c_int,
+    mode: mode_t,
+) -> c_int {
+    telemetry::call_intercepted();
+    let callback =
+        unsafe { mem::transmute::<*const (), OpenatCallback>(get_callback(&OPENAT_CALLBACK)) };
+    let original_openat = unsafe {
+        mem::transmute::<*const (), OpenatFunction>(get_original_function(
+            "openat",
+            &ORIGINAL_OPENAT,
+        ))
+    };
+    into_libc_result(callback(original_openat, dirfd, path, flags, mode))
+}
+
+extern "C" fn write_trampoline(fd: c_int, buf: *const c_void, count: size_t) -> ssize_t {
+    telemetry::call_intercepted();
+    let callback =
+        unsafe { mem::transmute::<*const (), WriteCallback>(get_callback(&WRITE_CALLBACK)) };
+    let original_write = unsafe {
+        mem::transmute::<*const (), WriteFunction>(get_original_function("write", &ORIGINAL_WRITE))
+    };
+    into_libc_result(callback(original_write, fd, buf, count))
+}
+
+extern "C" fn send_trampoline(
+    sockfd: c_int,
+    buf: *const c_void,
+    len: size_t,
+    flags: c_int,
+) -> ssize_t {
+    telemetry::call_intercepted();
+    let callback =
+        unsafe { mem::transmute::<*const (), SendCallback>(get_callback(&SEND_CALLBACK)) };
+    let original_send = unsafe {
+        mem::transmute::<*const (), SendFunction>(get_original_function("send", &ORIGINAL_SEND))
+    };
+    into_libc_result(callback(original_send, sockfd, buf, len, flags))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::CString;
+    use std::sync::Mutex;
+
+    use super::*;
+
+    // The callbacks are global.
+    static CALLBACK_LOCK: Mutex<()> = Mutex::new(());
+
+    fn deny_open(
+        _original_open: OpenFunction,
+        _path: *const c_char,
+        _flags: c_int,
+        _mode: mode_t,
+    ) -> Result<c_int, Errno> {
+        Err(Errno::EACCES)
+    }
+
+    fn forward_open(
+        original_open: OpenFunction,
+        path: *const c_char,
+        flags: c_int,
+        mode: mode_t,
+    ) -> Result<c_int, Errno> {
+        Errno::result(unsafe { original_open(path, flags, mode) })
+    }
+
+    #[test]
+    fn translates_denied_calls_to_the_libc_convention() {
+        let _lock = CALLBACK_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let path = CString::new("/dev/null").unwrap();
+
+        OPEN_CALLBACK.store(deny_open as *const () as u64, Ordering::Release);
+        assert_eq!(-1, open_trampoline(path.as_ptr(), libc::O_RDONLY, 0));
+        assert_eq!(Errno::EACCES, Errno::last());
+
+        // Allowed calls reach the original function.
+        OPEN_CALLBACK.store(forward_open as *const () as u64, Ordering::Release);
+        let fd = open_trampoline(path.as_ptr(), libc::O_RDONLY, 0);
+        assert!(0 <= fd);
+        unsafe { libc::close(fd) };
+    }
+
+    #[test]
+    fn refuses_hooking_hooked_functions() {
+        let _lock = CALLBACK_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+
+        OPEN_CALLBACK.store(deny_open as *const () as u64, Ordering::Release);
+        OPEN_HOOKED.store(true, Ordering::Release);
+        let result = install(ErrnoCallback::Open(forward_open));
+        OPEN_HOOKED.store(false, Ordering::Release);
+
+        assert!(matches!(
+            result,
+            Err(error::Error::CallbackAlreadyInstalled(f)) if "open" == f
+        ));
+
+        // The live hook's callback was kept.
+        assert_eq!(deny_open as *const (), get_callback(&OPEN_CALLBACK));
+    }
+}
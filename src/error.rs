@@ -4,23 +4,52 @@ use std::str::Utf8Error;
 use nix::errno::Errno;
 use procfs::ProcError;
 
+use crate::search_strategy::SearchStrategy;
+
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
     #[error("dladdr address ({0:p}) failed")]
     Dladdr(*const ()),
 
+    #[error("failed loading module [{0}]: {1}")]
+    LoadModule(String, String),
+
+    #[error("failed finding symbol [{1}] in module [{0}]: {2}")]
+    FindModuleSymbol(String, String, String),
+
+    #[error("failed finding global symbol [{0}]: {1}")]
+    FindGlobalSymbol(String, String),
+
+    #[error("failed finding the main executable")]
+    FindMainExecutable,
+
     #[error("failed finding current process in '/proc': {0}")]
     FindCurrentProcess(#[source] ProcError),
 
     #[error("failed reading process maps: {0}")]
     ReadProcessMaps(#[source] ProcError),
 
+    #[error("no module [{0}] is loaded")]
+    NoLoadedModule(String),
+
+    #[error("module at [{0:x}] isn't backed by a file")]
+    ModuleHasNoFile(u64),
+
+    #[error("failed reading module file [{0}]: {1}")]
+    ReadModuleFile(String, String),
+
     #[error("failed parsing ELF header: {0}")]
     ParseElfHeader(#[source] object::Error),
 
     #[error("failed getting ELF endianness: {0}")]
     GetElfEndianness(#[source] object::Error),
 
+    #[error("unsupported ELF machine ({0})")]
+    UnsupportedElfMachine(u16),
+
+    #[error("unsupported PowerPC64 ABI ({0}), only ELFv2 is supported")]
+    UnsupportedPowerPc64Abi(u32),
+
     #[error("ELF has no program headers")]
     ElfHasNoProgramHeaders,
 
@@ -30,9 +59,21 @@ pub enum Error {
     #[error("failed reading ELF program headers")]
     ReadElfProgramHeaders,
 
+    #[error("ELF has no loadable segments")]
+    ElfHasNoLoadableSegments,
+
+    #[error("ELF is linked at address [{0:x}], only position-independent ELFs are supported")]
+    UnsupportedElfLinkAddress(u64),
+
+    #[error("failed reading ELF sections: {0}")]
+    ReadElfSections(#[source] object::Error),
+
     #[error("ELF has no dynamic segment")]
     ElfHasNoDynamicSegment,
 
+    #[error("ELF has multiple ({0}) dynamic segments")]
+    MultipleDynamicSegments(usize),
+
     #[error("failed reading ELF dynamic segment")]
     ReadElfDynamicSegment,
 
@@ -45,23 +86,120 @@ pub enum Error {
     #[error("failed reading ELF PLT relocation table")]
     ReadElfPltRelocationTable,
 
+    #[error("failed reading ELF relocation table")]
+    ReadElfRelocationTable,
+
+    #[error("ELF has no dynamic symbol table")]
+    ElfHasNoDynamicSymbolTable,
+
+    #[error("ELF has no dynamic string table")]
+    ElfHasNoDynamicStringTable,
+
     #[error("failed reading ELF dynamic string table")]
     ReadElfDynamicStringTable,
 
+    #[error("failed reading ELF hash table")]
+    ReadElfHashTable,
+
+    #[error("cannot determine the number of ELF dynamic symbols without a hash table")]
+    CannotDetermineSymbolCount,
+
+    #[error("ELF symbol index ({0}) is out of bounds")]
+    SymbolIndexOutOfBounds(u32),
+
     #[error("failed reading ELF symbol")]
     ReadElfSymbol,
 
     #[error("failed finding ELF symbol name: {0}")]
     FindElfSymbolName(#[source] object::Error),
 
+    #[error("invalid ELF symbol name offset ({0})")]
+    InvalidElfSymbolName(usize),
+
     #[error("ELF symbol name is not UTF8: {0}")]
     NonUtf8ElfSymbolName(#[source] Utf8Error),
 
+    #[error("no dynamic symbol [{0}] in ELF")]
+    NoElfDynamicSymbol(String),
+
+    #[error("callback is null")]
+    NullCallback,
+
+    #[error("the original function of [{0}] is null")]
+    NullOriginalFunction(String),
+
     #[error("no GOT entry for function [{0}]")]
     NoGotEntryForFunction(String),
 
+    #[error(
+        "no search strategy found a GOT entry for function [{0}]: {}",
+        describe_failures(.1)
+    )]
+    NoSearchStrategySucceeded(String, Vec<(SearchStrategy, Error)>),
+
+    #[error("refusing to hook function [{0}] in the dynamic linker")]
+    RefusingToHookDynamicLinker(String),
+
+    #[error("failed verifying the write to GOT entry [{0:x}]")]
+    GotWriteVerificationFailed(u64),
+
+    #[error("GOT entry [{0:x}] was hooked by someone else, not restoring it")]
+    GotEntryRehooked(u64),
+
+    #[error("no trace prototype for function [{0}]")]
+    NoTracePrototype(String),
+
+    #[error("function [{0}] is already hooked with this crate's callback")]
+    CallbackAlreadyInstalled(String),
+
+    #[error("a file-system sandbox is already active")]
+    SandboxAlreadyActive,
+
+    #[error("no memory mapping contains address [{0:x}]")]
+    NoMappingForAddress(u64),
+
     #[error("failed modifying memory page [{1:x}] protection: {0}")]
     ModifyMemoryPageProtection(Errno, u64),
 }
 
 pub type Result<T> = result::Result<T, Error>;
+
+fn describe_failures(failures: &[(SearchStrategy, Error)]) -> String {
+    failures
+        .iter()
+        .map(|(strategy, error)| format!("{strategy}: {error}"))
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn describes_why_each_search_strategy_failed() {
+        let error = Error::NoSearchStrategySucceeded(
+            String::from("open"),
+            vec![
+                (
+                    SearchStrategy::PltRelocation,
+                    Error::NoGotEntryForFunction(String::from("open")),
+                ),
+                (
+                    SearchStrategy::OnDiskRelocation,
+                    Error::ModuleHasNoFile(0x1000),
+                ),
+            ],
+        );
+
+        assert_eq!(
+            format!(
+                "no search strategy found a GOT entry for function [open]: \
+                 PLT relocation: {}; on-disk relocation: {}",
+                Error::NoGotEntryForFunction(String::from("open")),
+                Error::ModuleHasNoFile(0x1000),
+            ),
+            error.to_string()
+        );
+    }
+}
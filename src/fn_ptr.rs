@@ -0,0 +1,59 @@
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// A C function pointer type, the type of a callback hooked with
+/// [`GotHook::new_typed`](crate::GotHook::new_typed).
+///
+/// Implemented for `extern "C" fn` and `unsafe extern "C" fn` types of up to
+/// eight arguments, and sealed against other implementations.
+///
+/// # Safety
+///
+/// Implementors must be pointer-sized, and valid for any non-null address.
+pub unsafe trait FnPtr: Copy + sealed::Sealed {
+    fn as_ptr(self) -> *const ();
+
+    /// # Safety
+    ///
+    /// `address` must be non-null, and hold a function of this type.
+    unsafe fn from_ptr(address: *const ()) -> Self;
+}
+
+macro_rules! impl_fn_ptr {
+    ($($argument:ident),*) => {
+        impl<R, $($argument),*> sealed::Sealed for extern "C" fn($($argument),*) -> R {}
+
+        unsafe impl<R, $($argument),*> FnPtr for extern "C" fn($($argument),*) -> R {
+            fn as_ptr(self) -> *const () {
+                self as *const ()
+            }
+
+            unsafe fn from_ptr(address: *const ()) -> Self {
+                unsafe { std::mem::transmute::<*const (), Self>(address) }
+            }
+        }
+
+        impl<R, $($argument),*> sealed::Sealed for unsafe extern "C" fn($($argument),*) -> R {}
+
+        unsafe impl<R, $($argument),*> FnPtr for unsafe extern "C" fn($($argument),*) -> R {
+            fn as_ptr(self) -> *const () {
+                self as *const ()
+            }
+
+            unsafe fn from_ptr(address: *const ()) -> Self {
+                unsafe { std::mem::transmute::<*const (), Self>(address) }
+            }
+        }
+    };
+}
+
+impl_fn_ptr!();
+impl_fn_ptr!(A);
+impl_fn_ptr!(A, B);
+impl_fn_ptr!(A, B, C);
+impl_fn_ptr!(A, B, C, D);
+impl_fn_ptr!(A, B, C, D, E);
+impl_fn_ptr!(A, B, C, D, E, F);
+impl_fn_ptr!(A, B, C, D, E, F, G);
+impl_fn_ptr!(A, B, C, D, E, F, G, H);
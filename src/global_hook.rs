@@ -0,0 +1,247 @@
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use crate::error;
+use crate::module_info;
+use crate::search_strategy::SearchStrategy;
+use crate::{GotHook, HookSet};
+
+/// What happened to a module examined by [`GlobalHook::install_detailed`].
+#[derive(Debug)]
+pub enum ScanOutcome {
+    /// The function was hooked in the module.
+    Hooked,
+
+    /// The module doesn't import the function, or may not be hooked (e.g. the
+    /// dynamic linker).
+    Skipped(error::Error),
+
+    /// The module couldn't be examined, e.g. because it isn't a supported ELF.
+    Failed(error::Error),
+}
+
+/// A module examined by [`GlobalHook::install_detailed`], in scan order.
+#[derive(Debug)]
+pub struct ModuleScan {
+    pub path: PathBuf,
+    pub base_address: u64,
+    pub outcome: ScanOutcome,
+
+    /// How long examining (and hooking) the module took.
+    pub duration: Duration,
+}
+
+/// Hooks a function in every loaded module that imports it.
+///
+/// Created with [`GotHook::global`].
+#[derive(Clone, Debug)]
+pub struct GlobalHook {
+    function_name: String,
+    callback: *const (),
+    max_modules: Option<usize>,
+    allow_dynamic_linker: bool,
+}
+
+impl GlobalHook {
+    pub(crate) fn new(function_name: &str, callback: *const ()) -> Self {
+        Self {
+            function_name: String::from(function_name),
+            callback,
+            max_modules: None,
+            allow_dynamic_linker: false,
+        }
+    }
+
+    /// Stops after hooking the function in the first `max_modules` modules
+    /// that import it, in address order.
+    ///
+    /// With a `max_modules` of 0, installing returns an empty [`HookSet`].
+    pub fn max_modules(mut self, max_modules: usize) -> Self {
+        self.max_modules = Some(max_modules);
+        self
+    }
+
+    /// Whether to hook the function in the dynamic linker as well, which is
+    /// skipped by default.
+    ///
+    /// Hooking functions the dynamic linker uses while it's resolving
+    /// symbols can deadlock or crash it.
+    pub fn allow_dynamic_linker(mut self, allow: bool) -> Self {
+        self.allow_dynamic_linker = allow;
+        self
+    }
+
+    /// Hooks the function, failing with [`error::Error::NoGotEntryForFunction`]
+    /// when no loaded module imports it.
+    ///
+    /// If any hook fails to install, the hooks installed before it are removed
+    /// before its error is returned.
+    pub fn install(self) -> error::Result<HookSet> {
+        self.install_detailed().map(|(hooks, _)| hooks)
+    }
+
+    /// Hooks the function like [`GlobalHook::install`], also returning every
+    /// module that was examined and what happened to it.
+    pub fn install_detailed(self) -> error::Result<(HookSet, Vec<ModuleScan>)> {
+        let max_modules = self.max_modules.unwrap_or(usize::MAX);
+        let mut hooks = Vec::new();
+        let mut scans = Vec::new();
+
+        // Nothing to hook, the modules needn't even be listed.
+        if 0 == max_modules {
+            return Ok((HookSet::from_hooks(hooks), scans));
+        }
+
+        for module in module_info::loaded_modules()? {
+            if hooks.len() >= max_modules {
+                break;
+            }
+
+            let start = Instant::now();
+            let outcome = match GotHook::resolve_got_entry(
+                module.base_address,
+                &self.function_name,
+                SearchStrategy::PltRelocation,
+            ) {
+                Ok(target) => {
+                    // Dropping the partially-installed hooks rolls them back.
+                    match GotHook::install(
+                        &self.function_name,
+                        target,
+                        self.callback,
+                        self.allow_dynamic_linker,
+                    ) {
+                        Ok(hook) => {
+                            hooks.push(hook);
+                            ScanOutcome::Hooked
+                        }
+                        // Skip the dynamic linker unless it's explicitly allowed.
+                        Err(e @ error::Error::RefusingToHookDynamicLinker(_)) => {
+                            ScanOutcome::Skipped(e)
+                        }
+                        Err(e) => return Err(e),
+                    }
+                }
+                // Skip modules that don't import the function, or that we can't parse.
+                Err(e @ error::Error::NoGotEntryForFunction(_)) => ScanOutcome::Skipped(e),
+                Err(e) => ScanOutcome::Failed(e),
+            };
+
+            scans.push(ModuleScan {
+                path: module.path,
+                base_address: module.base_address,
+                outcome,
+                duration: start.elapsed(),
+            });
+        }
+
+        if hooks.is_empty() {
+            return Err(error::Error::NoGotEntryForFunction(self.function_name));
+        }
+
+        Ok((HookSet::from_hooks(hooks), scans))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use object::elf;
+
+    use super::*;
+    use crate::test_elf::{self, ElfBuilder};
+
+    const FUNCTION_NAME: &str = "gothook_max_modules";
+    const CALLBACK: u64 = 0x2000;
+
+    #[test]
+    fn stops_after_the_maximal_number_of_modules() {
+        let _lock = test_elf::lock_loaded_modules();
+
+        let mut images: Vec<_> = (0..3)
+            .map(|i| {
+                ElfBuilder::new(elf::EM_AARCH64)
+                    .import(FUNCTION_NAME)
+                    .build()
+                    .map_file(&format!("libgothook-max-modules-{i}.so"))
+            })
+            .collect();
+        images.sort_by_key(|i| i.base_address());
+
+        let (hooks, scans) = GotHook::global(FUNCTION_NAME, CALLBACK as *const ())
+            .max_modules(2)
+            .install_detailed()
+            .unwrap();
+        assert_eq!(2, hooks.hooks().len());
+
+        // The modules are scanned in address order, up to the second hook.
+        let hooked: Vec<u64> = images
+            .iter()
+            .filter(|i| CALLBACK == i.read_got_entry(FUNCTION_NAME))
+            .map(|i| i.base_address())
+            .collect();
+        assert_eq!(
+            [images[0].base_address(), images[1].base_address()],
+            *hooked
+        );
+        assert_eq!(
+            Some(images[1].base_address()),
+            scans.last().map(|s| s.base_address)
+        );
+        assert!(!scans
+            .iter()
+            .any(|s| images[2].base_address() == s.base_address));
+    }
+
+    #[test]
+    fn scans_every_loaded_module() {
+        let _lock = test_elf::lock_loaded_modules();
+
+        let image = ElfBuilder::new(elf::EM_AARCH64)
+            .import("gothook_scanned")
+            .build()
+            .map_file("libgothook-scanned.so");
+        let modules = module_info::loaded_modules().unwrap();
+
+        let (_hooks, scans) =
+            GotHook::new_global_detailed("gothook_scanned", CALLBACK as *const ()).unwrap();
+
+        // Every module is scanned, in address order.
+        assert_eq!(
+            modules
+                .iter()
+                .map(|m| (m.path.clone(), m.base_address))
+                .collect::<Vec<_>>(),
+            scans
+                .iter()
+                .map(|s| (s.path.clone(), s.base_address))
+                .collect::<Vec<_>>()
+        );
+        let test_binary = env::current_exe().unwrap();
+        assert!(scans.iter().any(|s| test_binary == s.path));
+        assert!(scans.iter().any(|s| s
+            .path
+            .file_name()
+            .is_some_and(|n| n.to_string_lossy().starts_with("libc"))));
+
+        for scan in scans {
+            if image.base_address() == scan.base_address {
+                assert!(matches!(scan.outcome, ScanOutcome::Hooked));
+            } else {
+                assert!(!matches!(scan.outcome, ScanOutcome::Hooked));
+            }
+        }
+    }
+
+    #[test]
+    fn hooks_nothing_in_no_modules() {
+        let (hooks, scans) = GotHook::global(FUNCTION_NAME, CALLBACK as *const ())
+            .max_modules(0)
+            .install_detailed()
+            .unwrap();
+
+        assert!(hooks.hooks().is_empty());
+        assert!(scans.is_empty());
+    }
+}
@@ -1,76 +1,319 @@
-use std::ffi::c_void;
+use std::ffi::{c_char, c_void, CStr, CString};
 use std::mem;
 use std::ptr;
-use std::slice;
-use std::str;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 
 use libc::Dl_info;
-use nix::sys::mman::{self, ProtFlags};
-use object::elf::{self, Dyn64, FileHeader64, ProgramHeader64, Rela64, Sym64};
+use object::elf::{self, FileHeader64};
 use object::endian::Endianness;
-use object::read::elf::{Dyn, FileHeader, ProgramHeader, Rela, Sym};
-use object::read::StringTable;
+use object::read::elf::ProgramHeader;
 use object::ReadRef;
-use procfs::process::Process;
 
+use crate::callback_info::CallbackInfo;
+use crate::dynamic_segment;
+use crate::elf_image::ElfImage;
+use crate::errno_hook::{self, ErrnoCallback};
 use crate::error;
-
-const PAGE_SIZE: usize = 4096;
+use crate::fn_ptr::FnPtr;
+use crate::global_hook::{GlobalHook, ModuleScan};
+use crate::hook_set::HookSet;
+use crate::hook_state::HookState;
+use crate::module_info;
+use crate::on_disk_relocations;
+use crate::protection::Protection;
+use crate::registry;
+use crate::relocations::RelocationSource;
+use crate::resolved_target::{MatchedRelocation, ResolvedTarget};
+use crate::search_strategy::{SearchReport, SearchStrategy};
+use crate::telemetry;
+use crate::trace;
 
 pub struct GotHook {
+    function_name: String,
+    module_path: Option<String>,
     got_entry: u64,
     original_function: u64,
+    callback: u64,
+    relocation: Option<MatchedRelocation>,
+    active: bool,
+    // Where the crate's own callbacks read the original function from.
+    original_function_mirror: Option<&'static AtomicU64>,
+    // Set while the crate's own callback is installed.
+    installed_flag: Option<&'static AtomicBool>,
 }
 
 impl GotHook {
     pub fn new(function_name: &str, callback: *const ()) -> error::Result<Self> {
+        Self::check_callback(callback)?;
+
         // Retrieve the callback symbolic information.
         let callback_information = Self::get_address_symbolic_information(callback)?;
 
-        // Find the callback ELF in memory.
-        let elf_data = Self::find_elf_in_memory(callback_information.dli_fbase as u64)?;
+        // Hook the function in the callback's ELF.
+        Self::new_with_base(
+            callback_information.dli_fbase as u64,
+            function_name,
+            callback,
+        )
+    }
 
-        // Parse the ELF's header.
-        let elf_header: &FileHeader64<Endianness> =
-            FileHeader64::parse(elf_data).map_err(error::Error::ParseElfHeader)?;
+    /// Hooks `function_name` in the callback's ELF like [`GotHook::new`], and
+    /// returns the original function as the callback's type, ready to call.
+    ///
+    /// `F` must be the hooked function's function pointer type (e.g.
+    /// `extern "C" fn(c_int) -> c_int`). Fails with
+    /// [`error::Error::NullOriginalFunction`], leaving the function unhooked,
+    /// when the GOT entry is null (e.g. for an unresolved weak symbol).
+    pub fn new_typed<F: FnPtr>(function_name: &str, callback: F) -> error::Result<(Self, F)> {
+        Self::new_typed_with(callback, |callback| Self::new(function_name, callback))
+    }
 
-        // Get the ELF's endianness.
-        let elf_endian = elf_header
-            .endian()
-            .map_err(error::Error::GetElfEndianness)?;
+    fn new_typed_with<F: FnPtr, H: FnOnce(*const ()) -> error::Result<Self>>(
+        callback: F,
+        hook: H,
+    ) -> error::Result<(Self, F)> {
+        // Hook the function with the callback.
+        let hook = hook(callback.as_ptr())?;
 
-        // Locate the ELF's dynamic segment.
-        let elf_dynamic_segment = Self::find_elf_dynamic_segment(elf_data, elf_header, elf_endian)?;
+        // Give the original function the callback's type. Dropping the hook
+        // restores the null GOT entry.
+        let original_function = hook.get_original_function();
+        if original_function.is_null() {
+            return Err(error::Error::NullOriginalFunction(
+                hook.function_name.clone(),
+            ));
+        }
 
-        // Locate the ELF's PLT relocation table.
-        let elf_plt_relocation_table =
-            Self::find_elf_plt_relocation_table(elf_data, elf_dynamic_segment, elf_endian)?;
+        Ok((hook, unsafe { F::from_ptr(original_function) }))
+    }
 
-        // Locate the ELF's dynamic string table.
-        let elf_dynamic_string_table =
-            Self::find_elf_dynamic_string_table(elf_data, elf_dynamic_segment, elf_endian)?;
+    /// Hooks `function_name` in the main executable with the function
+    /// exported as `callback_symbol` by `callback_module`.
+    ///
+    /// `callback_module` is loaded if it isn't already, and is never unloaded.
+    pub fn new_callback_symbol(
+        function_name: &str,
+        callback_module: &str,
+        callback_symbol: &str,
+    ) -> error::Result<Self> {
+        // Resolve the callback in its module.
+        let callback = Self::find_module_symbol(callback_module, callback_symbol)?;
 
-        // Locate the function's GOT entry.
-        let function_got_entry = Self::find_elf_function_got_entry(
+        // Hook the function in the main executable.
+        Self::new_in_main_executable(function_name, callback)
+    }
+
+    /// Hooks `function_name` in the main executable with a callback that
+    /// records each call (see [`take_trace_records`](crate::take_trace_records))
+    /// before forwarding it to the original function.
+    ///
+    /// Supported functions: `write`, `send`, `sendfile` and `copy_file_range`.
+    ///
+    /// A traced function can't be traced again, failing with
+    /// [`error::Error::CallbackAlreadyInstalled`], until its hook is removed.
+    pub fn trace(function_name: &str) -> error::Result<Self> {
+        trace::install(function_name)
+    }
+
+    /// Hooks `function_name` in every loaded module that imports it.
+    pub fn new_global(function_name: &str, callback: *const ()) -> error::Result<HookSet> {
+        Self::global(function_name, callback).install()
+    }
+
+    /// Hooks `function_name` in every loaded module that imports it, also
+    /// returning every module that was examined and what happened to it.
+    pub fn new_global_detailed(
+        function_name: &str,
+        callback: *const (),
+    ) -> error::Result<(HookSet, Vec<ModuleScan>)> {
+        Self::global(function_name, callback).install_detailed()
+    }
+
+    /// Configures hooking `function_name` in every loaded module that imports
+    /// it, e.g. to stop after the first few modules.
+    pub fn global(function_name: &str, callback: *const ()) -> GlobalHook {
+        GlobalHook::new(function_name, callback)
+    }
+
+    /// Hooks the function `callback` was written for in the main executable,
+    /// translating the errors it returns to the libc convention.
+    ///
+    /// Each function has a single callback: hooking it again fails with
+    /// [`error::Error::CallbackAlreadyInstalled`] until the previous hook is
+    /// removed.
+    pub fn new_errno(callback: ErrnoCallback) -> error::Result<Self> {
+        errno_hook::install(callback)
+    }
+
+    /// Hooks `function_name` in the main executable's GOT only, leaving the
+    /// calls the loaded libraries make to it untouched.
+    ///
+    /// The main executable is located through its program headers (the
+    /// `AT_PHDR` auxiliary vector entry).
+    pub fn new_in_main_executable(function_name: &str, callback: *const ()) -> error::Result<Self> {
+        let main_executable_base = Self::find_main_executable_base()?;
+        Self::new_with_base(main_executable_base, function_name, callback)
+    }
+
+    /// Hooks `function_name` in the callback's ELF, trying every
+    /// [`SearchStrategy`] in order until one locates its GOT entry.
+    pub fn new_auto(
+        function_name: &str,
+        callback: *const (),
+    ) -> error::Result<(Self, SearchReport)> {
+        Self::check_callback(callback)?;
+
+        // Retrieve the callback symbolic information.
+        let callback_information = Self::get_address_symbolic_information(callback)?;
+
+        Self::new_auto_with_base(
             callback_information.dli_fbase as u64,
-            elf_data,
-            elf_dynamic_segment,
-            elf_plt_relocation_table,
-            elf_dynamic_string_table,
-            elf_endian,
             function_name,
-        )?;
+            callback,
+        )
+    }
+
+    /// Hooks `function_name` in the ELF image loaded at `base_address`, trying
+    /// every [`SearchStrategy`] in order until one locates its GOT entry.
+    pub fn new_auto_with_base(
+        base_address: u64,
+        function_name: &str,
+        callback: *const (),
+    ) -> error::Result<(Self, SearchReport)> {
+        Self::check_callback(callback)?;
 
-        // Backup the original function.
-        let function_got_entry_pointer = function_got_entry as *const *const ();
-        let original_function = unsafe { *function_got_entry_pointer };
+        // Try each search strategy, remembering why the previous ones failed.
+        let mut failures = Vec::new();
+        for strategy in SearchStrategy::ALL {
+            match Self::resolve_got_entry(base_address, function_name, strategy) {
+                Ok(target) => {
+                    let hook = Self::install(function_name, target, callback, false)?;
+                    return Ok((hook, SearchReport { strategy, failures }));
+                }
+                Err(e) => failures.push((strategy, e)),
+            }
+        }
+
+        Err(error::Error::NoSearchStrategySucceeded(
+            String::from(function_name),
+            failures,
+        ))
+    }
+
+    /// Hooks `function_name` in the ELF image loaded at `base_address`.
+    ///
+    /// Refuses to hook the dynamic linker, see
+    /// [`ModuleHooker::allow_dynamic_linker`](crate::ModuleHooker::allow_dynamic_linker).
+    ///
+    /// The image doesn't have to be backed by a file (e.g. when a JIT maps
+    /// it into anonymous memory), but it must be mapped in its entirety.
+    pub fn new_with_base(
+        base_address: u64,
+        function_name: &str,
+        callback: *const (),
+    ) -> error::Result<Self> {
+        // Locate the function's GOT entry.
+        let target =
+            Self::resolve_got_entry(base_address, function_name, SearchStrategy::PltRelocation)?;
+
+        Self::install(function_name, target, callback, false)
+    }
+
+    pub(crate) fn install(
+        function_name: &str,
+        target: ResolvedTarget,
+        callback: *const (),
+        allow_dynamic_linker: bool,
+    ) -> error::Result<Self> {
+        Self::check_callback(callback)?;
+
+        // Hooking the dynamic linker while it may still be resolving
+        // symbols can deadlock or crash it.
+        if !allow_dynamic_linker && module_info::is_in_dynamic_linker(target.got_entry)? {
+            return Err(error::Error::RefusingToHookDynamicLinker(String::from(
+                function_name,
+            )));
+        }
+
+        // Backup the original function, and its page's protection.
+        registry::acquire_page(target.got_entry)?;
+        let got_entry_pointer = target.got_entry as *const *const ();
+        let original_function = unsafe { *got_entry_pointer };
 
         // Hook the function with the callback.
-        Self::hook_got_entry(function_got_entry, callback)?;
+        if let Err(e) = Self::hook_got_entry(target.got_entry, callback) {
+            // The hook's error is the one worth reporting.
+            let _ = registry::release_page(target.got_entry);
+            return Err(e);
+        }
+        registry::register(target.got_entry);
+        telemetry::hook_installed();
 
         Ok(Self {
-            got_entry: function_got_entry,
+            function_name: String::from(function_name),
+            module_path: target.module_path,
+            got_entry: target.got_entry,
             original_function: original_function as u64,
+            callback: callback as u64,
+            relocation: target.relocation,
+            active: true,
+            original_function_mirror: None,
+            installed_flag: None,
+        })
+    }
+
+    fn check_callback(callback: *const ()) -> error::Result<()> {
+        // A null GOT entry crashes the next call to the function.
+        if callback.is_null() {
+            return Err(error::Error::NullCallback);
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn resolve_got_entry(
+        base_address: u64,
+        function_name: &str,
+        strategy: SearchStrategy,
+    ) -> error::Result<ResolvedTarget> {
+        // Find the ELF in memory.
+        let image = ElfImage::find(base_address)?;
+        let module_path = Self::find_module_path(base_address)?;
+
+        if SearchStrategy::GotScan == strategy {
+            return Self::scan_elf_got(&image, function_name).map(|got_entry| ResolvedTarget {
+                module_path,
+                got_entry,
+                relocation: None,
+            });
+        }
+
+        if SearchStrategy::OnDiskRelocation == strategy {
+            let path = module_path
+                .as_deref()
+                .ok_or(error::Error::ModuleHasNoFile(base_address))?;
+            let (got_entry, relocation) = on_disk_relocations::find_got_entry(
+                base_address,
+                path,
+                image.machine,
+                function_name,
+            )?;
+
+            return Ok(ResolvedTarget {
+                module_path,
+                got_entry,
+                relocation: Some(relocation),
+            });
+        }
+
+        // Locate the function's GOT entry.
+        let (got_entry, relocation) =
+            Self::find_elf_function_got_entry(&image, function_name, strategy)?;
+
+        Ok(ResolvedTarget {
+            module_path,
+            got_entry,
+            relocation: Some(relocation),
         })
     }
 
@@ -78,6 +321,162 @@ impl GotHook {
         self.original_function as *const ()
     }
 
+    /// Returns the index, within its relocation table, of the relocation the
+    /// GOT entry was located through, if any.
+    pub fn relocation_index(&self) -> Option<usize> {
+        self.relocation.map(|r| r.index)
+    }
+
+    /// Returns the raw `r_offset` of the relocation the GOT entry was located
+    /// through, if any, as listed by `readelf -r`.
+    pub fn relocation_offset(&self) -> Option<u64> {
+        self.relocation.map(|r| r.offset)
+    }
+
+    /// Returns the explicit addend of the relocation the GOT entry was located
+    /// through, if it's a RELA relocation.
+    pub fn relocation_addend(&self) -> Option<i64> {
+        self.relocation.and_then(|r| r.addend)
+    }
+
+    pub fn state(&self) -> HookState {
+        HookState {
+            function_name: self.function_name.clone(),
+            module_path: self.module_path.clone(),
+            got_entry: self.got_entry,
+            original_function: self.original_function,
+            callback: self.callback,
+            relocation_type: self.relocation.map(|r| r.reloc_type),
+            relocation_index: self.relocation.map(|r| r.index),
+            relocation_offset: self.relocation.map(|r| r.offset),
+            active: Self::read_got_entry(self.got_entry) == self.callback,
+            page_protection_modified: registry::is_page_protection_modified(self.got_entry),
+        }
+    }
+
+    /// Returns the symbolic information of the callback, to confirm which
+    /// module it was resolved to.
+    pub fn callback_dl_info(&self) -> error::Result<CallbackInfo> {
+        let callback_information =
+            Self::get_address_symbolic_information(self.callback as *const ())?;
+
+        Ok(CallbackInfo {
+            module_name: Self::dl_string(callback_information.dli_fname).unwrap_or_default(),
+            module_base: callback_information.dli_fbase as u64,
+            symbol_name: Self::dl_string(callback_information.dli_sname),
+            symbol_address: callback_information.dli_saddr as u64,
+        })
+    }
+
+    /// Calls the original function once through `call`, so that it's
+    /// resolved by the time the callback forwards calls to it.
+    ///
+    /// Under lazy binding, a function that wasn't called yet has a GOT entry
+    /// pointing back into the PLT, so its original function is the dynamic
+    /// linker's resolution stub. Calling it resolves the function, writing it
+    /// to the GOT entry over our callback: the resolved function then becomes
+    /// the original function, and the callback is rewritten.
+    ///
+    /// `call` receives the original function and must call it with benign
+    /// arguments. Whatever side effects that call has do happen, which is why
+    /// priming is never done implicitly. On AArch64, the resolution stub is
+    /// shared by all the PLT entries: `call` must pass it the GOT entry's
+    /// address in `x16`, as the PLT stubs do.
+    ///
+    /// The callbacks this crate provides (the tracing, sandboxing and errno
+    /// hooks) forward calls to the primed function too.
+    pub fn prime_original<F: FnOnce(*const ())>(&mut self, call: F) -> error::Result<()> {
+        call(self.original_function as *const ());
+
+        // The GOT entry still holds our callback if the function was already
+        // resolved.
+        let current_function = Self::read_got_entry(self.got_entry);
+        if current_function == self.callback {
+            return Ok(());
+        }
+
+        // Adopt the resolved function, and hook the GOT entry again.
+        self.original_function = current_function;
+        if let Some(mirror) = self.original_function_mirror {
+            mirror.store(current_function, Ordering::Release);
+        }
+        Self::hook_got_entry(self.got_entry, self.callback as *const ())
+    }
+
+    /// Stores the original function in `mirror`, for a callback that calls it,
+    /// and keeps it up to date when the original function is primed.
+    pub(crate) fn mirror_original_function(&mut self, mirror: &'static AtomicU64) {
+        mirror.store(self.original_function, Ordering::Release);
+        self.original_function_mirror = Some(mirror);
+    }
+
+    /// Clears `flag` once the GOT entry is restored.
+    ///
+    /// A GOT entry that was hooked on top of us still calls our callback, so
+    /// `flag` is kept set when it can't be restored.
+    pub(crate) fn clear_on_restore(&mut self, flag: &'static AtomicBool) {
+        self.installed_flag = Some(flag);
+    }
+
+    /// Changes the protection of the GOT entry's page, keeping the hook
+    /// installed, e.g. to make it read-only again so that the entry can't be
+    /// tampered with.
+    ///
+    /// The protection applies to the whole page, including the other hooks on
+    /// it. Unhooking makes the page writable again to restore the GOT entry,
+    /// and the last hook on the page restores the protection it had before it
+    /// was hooked.
+    pub fn reprotect(&mut self, protection: Protection) -> error::Result<()> {
+        registry::protect_page(self.got_entry, protection)
+    }
+
+    /// Restores the GOT entry to the original function.
+    ///
+    /// Fails with [`error::Error::GotEntryRehooked`] when the GOT entry no
+    /// longer points to our callback, in which case the newer hook is kept.
+    pub fn unhook(mut self) -> error::Result<()> {
+        self.restore()
+    }
+
+    fn restore(&mut self) -> error::Result<()> {
+        if !self.active {
+            return Ok(());
+        }
+        self.active = false;
+        registry::unregister(self.got_entry);
+        telemetry::hook_removed();
+
+        let result = self.restore_got_entry();
+        if let (Ok(()), Some(flag)) = (&result, self.installed_flag) {
+            flag.store(false, Ordering::Release);
+        }
+
+        // Restore the page's original protection once its last hook is gone,
+        // even if this one couldn't be restored.
+        let release_result = registry::release_page(self.got_entry);
+
+        let result = result.and(release_result);
+        if result.is_err() {
+            telemetry::restore_failed();
+        }
+
+        result
+    }
+
+    fn restore_got_entry(&self) -> error::Result<()> {
+        // Make sure nobody hooked the GOT entry on top of us, restoring our
+        // original function would clobber their hook.
+        if Self::read_got_entry(self.got_entry) != self.callback {
+            return Err(error::Error::GotEntryRehooked(self.got_entry));
+        }
+
+        // The page may have been reprotected since the hook was installed.
+        registry::protect_page(self.got_entry, Protection::ReadWrite)?;
+
+        // Restore the GOT entry to the original function.
+        Self::write_got_entry(self.got_entry, self.original_function as *const ())
+    }
+
     fn get_address_symbolic_information(address: *const ()) -> error::Result<Dl_info> {
         let mut callback_information = Dl_info {
             dli_fname: ptr::null(),
@@ -98,176 +497,116 @@ impl GotHook {
         Ok(callback_information)
     }
 
-    fn find_elf_in_memory(base_address: u64) -> error::Result<&'static [u8]> {
-        // Locate the current process in '/proc'.
-        let process = Process::myself().map_err(error::Error::FindCurrentProcess)?;
+    fn find_module_symbol(module: &str, symbol: &str) -> error::Result<*const ()> {
+        let module_name = CString::new(module)
+            .map_err(|e| error::Error::LoadModule(String::from(module), e.to_string()))?;
+        let symbol_name = CString::new(symbol).map_err(|e| {
+            error::Error::FindModuleSymbol(
+                String::from(module),
+                String::from(symbol),
+                e.to_string(),
+            )
+        })?;
 
-        // Search for the ELF file in the process's maps.
-        let mut number_of_elf_mappings_found = 0;
-        let mut top_address = 0;
+        // Load the module, or get a handle to it if it's already loaded.
+        let handle = unsafe { libc::dlopen(module_name.as_ptr(), libc::RTLD_NOW) };
+        if handle.is_null() {
+            return Err(error::Error::LoadModule(
+                String::from(module),
+                Self::get_dl_error(),
+            ));
+        }
 
-        for map in process
-            .maps()
-            .map_err(error::Error::ReadProcessMaps)?
-            .iter()
-        {
-            // The ELF file is mapped into 4 sequenced mappings.
-            // Find the last one to compute the full ELF memory range.
-            if 0 == number_of_elf_mappings_found {
-                // Check if the current mapping if the ELF file.
-                if map.address.0 == base_address {
-                    number_of_elf_mappings_found = 1;
-                }
-            } else if number_of_elf_mappings_found <= 2 {
-                // This is a mapping between the first mapping and the last mapping.
-                number_of_elf_mappings_found += 1;
-            } else {
-                // This is the last mapping!
-                top_address = map.address.1;
-                break;
-            }
+        // Resolve the symbol.
+        let address = unsafe { libc::dlsym(handle, symbol_name.as_ptr()) };
+        if address.is_null() {
+            return Err(error::Error::FindModuleSymbol(
+                String::from(module),
+                String::from(symbol),
+                Self::get_dl_error(),
+            ));
         }
 
-        // Create a slice that contains the ELF in-memory.
-        Ok(unsafe {
-            slice::from_raw_parts(
-                base_address as *const u8,
-                (top_address - base_address) as usize,
-            )
-        })
+        Ok(address as *const ())
     }
 
-    fn find_elf_dynamic_segment<'a>(
-        data: &'a [u8],
-        header: &'a FileHeader64<Endianness>,
-        endian: Endianness,
-    ) -> error::Result<&'a [Dyn64<Endianness>]> {
-        // Find the dynamic segment program header.
-        let program_header = Self::get_elf_segments(data, header, endian)?
-            .iter()
-            .find(|&s| elf::PT_DYNAMIC == s.p_type(endian))
-            .ok_or(error::Error::ElfHasNoDynamicSegment)?;
+    fn get_dl_error() -> String {
+        Self::dl_string(unsafe { libc::dlerror() }).unwrap_or_default()
+    }
 
-        // Read the dynamic segment.
-        data.read_slice_at(
-            program_header.p_vaddr(endian),
-            program_header.p_memsz(endian) as usize / mem::size_of::<Dyn64<Endianness>>(),
+    fn dl_string(string: *const c_char) -> Option<String> {
+        if string.is_null() {
+            return None;
+        }
+
+        Some(
+            unsafe { CStr::from_ptr(string) }
+                .to_string_lossy()
+                .into_owned(),
         )
-        .map_err(|_| error::Error::ReadElfDynamicSegment)
     }
 
-    fn find_elf_plt_relocation_table<'a>(
-        data: &'a [u8],
-        dynamic_segment: &'a [Dyn64<Endianness>],
-        endian: Endianness,
-    ) -> error::Result<&'a [Rela64<Endianness>]> {
-        // Find the PLT relocation table address.
-        let address_entry = dynamic_segment
-            .iter()
-            .find(|&e| {
-                e.tag32(endian)
-                    .map(|t| elf::DT_JMPREL == t)
-                    .unwrap_or(false)
-            })
-            .ok_or(error::Error::ElfHasNoPltRelocationTable)?;
-        let address = address_entry.d_val(endian);
+    pub(crate) fn find_main_executable_base() -> error::Result<u64> {
+        // The main executable's program headers are mapped as part of it,
+        // so their symbolic information points at its base.
+        let program_headers = unsafe { libc::getauxval(libc::AT_PHDR) };
+        if 0 == program_headers {
+            return Err(error::Error::FindMainExecutable);
+        }
 
-        // Get the PLT relocation table size.
-        let size_entry = dynamic_segment
-            .iter()
-            .find(|&e| {
-                e.tag32(endian)
-                    .map(|t| elf::DT_PLTRELSZ == t)
-                    .unwrap_or(false)
-            })
-            .ok_or(error::Error::ElfHasNoPltRelocationTable)?;
-        let size = size_entry.d_val(endian);
+        let program_headers_information =
+            Self::get_address_symbolic_information(program_headers as *const ())?;
 
-        // Read the PLT relocation table.
-        data.read_slice_at(address, size as usize)
-            .map_err(|_| error::Error::ReadElfPltRelocationTable)
+        Ok(program_headers_information.dli_fbase as u64)
     }
 
-    fn find_elf_dynamic_string_table<'a>(
-        data: &'a [u8],
-        dynamic_segment: &'a [Dyn64<Endianness>],
-        endian: Endianness,
-    ) -> error::Result<StringTable<'a, &'a [u8]>> {
-        // Find the dynamic string table address.
-        let address_entry = dynamic_segment
-            .iter()
-            .find(|&e| {
-                e.tag32(endian)
-                    .map(|t| elf::DT_STRTAB == t)
-                    .unwrap_or(false)
-            })
-            .ok_or(error::Error::ElfHasNoPltRelocationTable)?;
-        let address = address_entry.d_val(endian);
-
-        // Find the dynamic string table size.
-        let size_entry = dynamic_segment
-            .iter()
-            .find(|&e| e.tag32(endian).map(|t| elf::DT_STRSZ == t).unwrap_or(false))
-            .ok_or(error::Error::ElfHasNoPltRelocationTable)?;
-        let size = size_entry.d_val(endian);
-
-        // Read the dynamic string table.
-        Ok(StringTable::new(data, address, address + size))
+    fn find_module_path(base_address: u64) -> error::Result<Option<String>> {
+        Ok(module_info::loaded_modules()?
+            .into_iter()
+            .find(|m| m.base_address == base_address)
+            .map(|m| m.path.to_string_lossy().into_owned()))
     }
 
     fn find_elf_function_got_entry(
-        base_address: u64,
-        data: &[u8],
-        dynamic_segment: &[Dyn64<Endianness>],
-        plt_relocation_table: &[Rela64<Endianness>],
-        dynamic_string_table: StringTable,
-        endian: Endianness,
+        image: &ElfImage,
         function_name: &str,
-    ) -> error::Result<u64> {
-        // Find the dynamic symbol table address.
-        let dynamic_symbol_table_address_entry = dynamic_segment
-            .iter()
-            .find(|&e| {
-                e.tag32(endian)
-                    .map(|t| elf::DT_SYMTAB == t)
-                    .unwrap_or(false)
-            })
-            .ok_or(error::Error::ElfHasNoPltRelocationTable)?;
-        let dynamic_symbol_table_address = dynamic_symbol_table_address_entry.d_val(endian);
-
-        // Search for the function's PLT relocation entry.
-        for relocation in plt_relocation_table.iter() {
-            // Skip non jump slot relocations.
-            if elf::R_AARCH64_JUMP_SLOT != relocation.r_type(endian, false) {
+        strategy: SearchStrategy,
+    ) -> error::Result<(u64, MatchedRelocation)> {
+        // Search for the function's relocation entry.
+        let machine = image.machine;
+        let mut relocations = image.relocations()?;
+        while let Some(relocation) = relocations.next() {
+            // Skip relocations the strategy doesn't consider.
+            let is_candidate = match strategy {
+                SearchStrategy::PltRelocation => {
+                    RelocationSource::Plt == relocation.source
+                        && machine.jump_slot_type() == relocation.reloc_type
+                }
+                SearchStrategy::GlobDatRelocation => {
+                    RelocationSource::Plt != relocation.source
+                        && machine.glob_dat_type() == relocation.reloc_type
+                }
+                SearchStrategy::OnDiskRelocation | SearchStrategy::GotScan => false,
+            };
+            if !is_candidate {
                 continue;
             }
 
-            // Retrieve the relocation's symbol index.
-            let symbol_index = relocation.r_sym(endian, false);
-
-            // Read the relocation's symbol.
-            let symbol: &Sym64<Endianness> = data
-                .read_at(
-                    dynamic_symbol_table_address
-                        + (symbol_index as u64 * mem::size_of::<Sym64<Endianness>>() as u64),
-                )
-                .map_err(|_| error::Error::ReadElfSymbol)?;
-
-            // Read the relocation's symbol name.
-            let symbol_name = str::from_utf8(
-                symbol
-                    .name(endian, dynamic_string_table)
-                    .map_err(error::Error::FindElfSymbolName)?,
-            )
-            .map_err(error::Error::NonUtf8ElfSymbolName)?;
-
             // Skip relocations that aren't the function.
-            if symbol_name != function_name {
+            if relocations.symbol_name(&relocation)? != Some(function_name) {
                 continue;
             }
 
-            // Find function's GOT entry address.
-            return Ok(base_address + relocation.r_offset(endian));
+            // Found the function's GOT entry address.
+            return Ok((
+                relocation.got_address,
+                MatchedRelocation {
+                    reloc_type: relocation.reloc_type,
+                    index: relocation.index,
+                    offset: relocation.offset,
+                    addend: relocation.addend,
+                },
+            ));
         }
 
         Err(error::Error::NoGotEntryForFunction(String::from(
@@ -275,59 +614,700 @@ impl GotHook {
         )))
     }
 
-    fn hook_got_entry(entry_address: u64, callback: *const ()) -> error::Result<()> {
-        // Ensure the GOT entry's page is writable.
-        // TODO: We really should backup the original page permissions and
-        // restore them after the hooking process is complete.
-        let got_entry_page = entry_address & (!(PAGE_SIZE as u64 - 1));
-        unsafe {
-            mman::mprotect(
-                got_entry_page as *mut c_void,
-                PAGE_SIZE,
-                ProtFlags::PROT_READ | ProtFlags::PROT_WRITE,
-            )
-            .map_err(|e| error::Error::ModifyMemoryPageProtection(e, got_entry_page))?
+    fn scan_elf_got(image: &ElfImage, function_name: &str) -> error::Result<u64> {
+        // Resolve the function's address the same way the dynamic linker would.
+        let function_address = Self::find_global_symbol(function_name)?;
+
+        // Search the ELF's GOT for a slot holding the function's address.
+        let word_size = mem::size_of::<u64>() as u64;
+        for (start, end) in Self::find_elf_got_ranges(image)? {
+            let start = (start + word_size - 1) & !(word_size - 1);
+            let Ok(slots) = image
+                .data
+                .read_slice_at::<u64>(start, (end.saturating_sub(start) / word_size) as usize)
+            else {
+                continue;
+            };
+
+            if let Some(index) = slots.iter().position(|&s| s == function_address) {
+                return Ok(image.base_address + start + index as u64 * word_size);
+            }
+        }
+
+        Err(error::Error::NoGotEntryForFunction(String::from(
+            function_name,
+        )))
+    }
+
+    fn find_elf_got_ranges(image: &ElfImage) -> error::Result<Vec<(u64, u64)>> {
+        // Without a readable dynamic segment, there's no telling where the GOT is.
+        let endian = image.endian;
+        let relro_segments = image
+            .segments()?
+            .iter()
+            .filter(|s| elf::PT_GNU_RELRO == s.p_type(endian));
+        let Ok(dynamic_segment) = image.dynamic_segment() else {
+            return Ok(Vec::new());
         };
+        let plt_got = dynamic_segment::find_dynamic_value::<FileHeader64<Endianness>>(
+            dynamic_segment,
+            endian,
+            elf::DT_PLTGOT,
+        );
 
-        // Hook the GOT entry with the callback.
-        let entry_pointer = entry_address as *mut *const ();
-        unsafe {
-            *entry_pointer = callback;
+        // The GOT starts with the first entry a jump slot or glob-dat
+        // relocation points at, or with the PLT's GOT.
+        let mut got_start = plt_got;
+        let mut jump_slots = 0;
+        if let Ok(relocations) = image.relocations() {
+            for relocation in relocations {
+                if RelocationSource::Plt == relocation.source {
+                    jump_slots += 1;
+                }
+                if image.machine.jump_slot_type() == relocation.reloc_type
+                    || image.machine.glob_dat_type() == relocation.reloc_type
+                {
+                    got_start =
+                        Some(got_start.map_or(relocation.offset, |s| s.min(relocation.offset)));
+                }
+            }
         }
 
-        Ok(())
+        // RELRO covers the GOT, once the dynamic linker relocated it, but
+        // also the data preceding it (e.g. `.data.rel.ro`, `.init_array` and
+        // `.dynamic`), which may hold function pointers too.
+        let mut ranges: Vec<(u64, u64)> = match got_start {
+            Some(got_start) => relro_segments
+                .map(|s| {
+                    (
+                        s.p_vaddr(endian).max(got_start),
+                        s.p_vaddr(endian) + s.p_memsz(endian),
+                    )
+                })
+                .filter(|&(start, end)| start < end)
+                .collect(),
+            None => Vec::new(),
+        };
+
+        // The PLT's GOT holds a jump slot per PLT relocation, following the
+        // reserved entries.
+        if let Some(plt_got) = plt_got {
+            let word_size = mem::size_of::<u64>() as u64;
+            ranges.push((
+                plt_got,
+                plt_got + (image.machine.reserved_plt_got_entries() + jump_slots) * word_size,
+            ));
+        }
+
+        Ok(ranges)
     }
 
-    fn get_elf_segments<'a>(
-        data: &'a [u8],
-        header: &'a FileHeader64<Endianness>,
-        endian: Endianness,
-    ) -> error::Result<&'a [ProgramHeader64<Endianness>]> {
-        // Get the ELF's program headers offset.
-        let program_headers_offset: u64 = header.e_phoff(endian).into();
-        if 0 == program_headers_offset {
-            return Err(error::Error::ElfHasNoProgramHeaders);
+    pub(crate) fn find_global_symbol(symbol: &str) -> error::Result<u64> {
+        let symbol_name = CString::new(symbol)
+            .map_err(|e| error::Error::FindGlobalSymbol(String::from(symbol), e.to_string()))?;
+
+        let address = unsafe { libc::dlsym(libc::RTLD_DEFAULT, symbol_name.as_ptr()) };
+        if address.is_null() {
+            return Err(error::Error::FindGlobalSymbol(
+                String::from(symbol),
+                Self::get_dl_error(),
+            ));
         }
 
-        // Get the number of program headers in the ELF.
-        let program_headers_number = header
-            .phnum(endian, data)
-            .map_err(error::Error::GetElfProgramHeadersNumber)?;
-        if 0 == program_headers_number {
-            return Err(error::Error::ElfHasNoProgramHeaders);
+        Ok(address as u64)
+    }
+
+    fn hook_got_entry(entry_address: u64, callback: *const ()) -> error::Result<()> {
+        // Ensure the GOT entry's page is writable.
+        registry::protect_page(entry_address, Protection::ReadWrite)?;
+
+        // Hook the GOT entry with the callback.
+        Self::write_got_entry(entry_address, callback)
+    }
+
+    fn read_got_entry(entry_address: u64) -> u64 {
+        unsafe { ptr::read_volatile(entry_address as *const u64) }
+    }
+
+    fn write_got_entry(entry_address: u64, function: *const ()) -> error::Result<()> {
+        Self::write_got_entry_with(entry_address, function, |entry_pointer, function| unsafe {
+            ptr::write_volatile(entry_pointer, function)
+        })
+    }
+
+    fn write_got_entry_with<W: FnMut(*mut *const (), *const ())>(
+        entry_address: u64,
+        function: *const (),
+        mut write: W,
+    ) -> error::Result<()> {
+        let entry_pointer = entry_address as *mut *const ();
+
+        // Verify the write actually happened: under a debugger, a watchpoint
+        // may race the write, so retry it once before giving up.
+        for _ in 0..2 {
+            write(entry_pointer, function);
+
+            if function == unsafe { ptr::read_volatile(entry_pointer) } {
+                return Ok(());
+            }
         }
 
-        data.read_slice_at(program_headers_offset, program_headers_number)
-            .map_err(|_| error::Error::ReadElfProgramHeaders)
+        Err(error::Error::GotWriteVerificationFailed(entry_address))
     }
 }
 
 impl Drop for GotHook {
     fn drop(&mut self) {
-        // Restore the GOT entry to the original function.
-        let got_entry_pointer = self.got_entry as *mut *const ();
-        unsafe {
-            *got_entry_pointer = self.original_function as *const ();
+        // A re-hooked GOT entry is left untouched, there's nothing more we can
+        // do about it here.
+        let _ = self.restore();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::path::Path;
+    use std::process;
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    use super::*;
+    use crate::test_elf::{self, ElfBuilder};
+
+    const ORIGINAL: u64 = 0x1000;
+    const CALLBACK: u64 = 0x2000;
+    const OTHER_CALLBACK: u64 = 0x3000;
+
+    #[test]
+    fn keeps_hooks_installed_on_top_of_ours() {
+        let image = ElfBuilder::new(elf::EM_AARCH64)
+            .import("read")
+            .build()
+            .map();
+        image.write_got_entry("read", ORIGINAL);
+
+        let hook =
+            GotHook::new_with_base(image.base_address(), "read", CALLBACK as *const ()).unwrap();
+        assert_eq!(CALLBACK, image.read_got_entry("read"));
+
+        // Another library hooks the function on top of us.
+        image.write_got_entry("read", OTHER_CALLBACK);
+
+        assert!(matches!(
+            hook.unhook(),
+            Err(error::Error::GotEntryRehooked(e)) if e == image.got_entry("read")
+        ));
+        assert_eq!(OTHER_CALLBACK, image.read_got_entry("read"));
+    }
+
+    #[test]
+    fn restores_stacked_hooks_in_reverse_order() {
+        let image = ElfBuilder::new(elf::EM_AARCH64)
+            .import("read")
+            .build()
+            .map();
+        image.write_got_entry("read", ORIGINAL);
+
+        let hook =
+            GotHook::new_with_base(image.base_address(), "read", CALLBACK as *const ()).unwrap();
+        let other_hook =
+            GotHook::new_with_base(image.base_address(), "read", OTHER_CALLBACK as *const ())
+                .unwrap();
+        assert_eq!(CALLBACK, other_hook.get_original_function() as u64);
+
+        other_hook.unhook().unwrap();
+        assert_eq!(CALLBACK, image.read_got_entry("read"));
+        hook.unhook().unwrap();
+        assert_eq!(ORIGINAL, image.read_got_entry("read"));
+    }
+
+    #[test]
+    fn finds_got_entries_only_reachable_through_the_got_scan() {
+        // A GOT entry whose relocation doesn't name the function.
+        let image = ElfBuilder::new(elf::EM_AARCH64)
+            .import("gothook_stripped")
+            .build()
+            .map();
+        let getpid = GotHook::find_global_symbol("getpid").unwrap();
+        image.write_got_entry("gothook_stripped", getpid);
+
+        for strategy in [
+            SearchStrategy::PltRelocation,
+            SearchStrategy::GlobDatRelocation,
+        ] {
+            assert!(matches!(
+                GotHook::resolve_got_entry(image.base_address(), "getpid", strategy),
+                Err(error::Error::NoGotEntryForFunction(_))
+            ));
+        }
+
+        let target =
+            GotHook::resolve_got_entry(image.base_address(), "getpid", SearchStrategy::GotScan)
+                .unwrap();
+        assert_eq!(image.got_entry("gothook_stripped"), target.got_entry);
+    }
+
+    #[test]
+    fn reports_the_strategies_tried_before_the_got_scan() {
+        let image = ElfBuilder::new(elf::EM_AARCH64)
+            .import("gothook_stripped")
+            .build()
+            .map();
+        let getpid = GotHook::find_global_symbol("getpid").unwrap();
+        image.write_got_entry("gothook_stripped", getpid);
+
+        let (hook, report) =
+            GotHook::new_auto_with_base(image.base_address(), "getpid", CALLBACK as *const ())
+                .unwrap();
+        assert_eq!(SearchStrategy::GotScan, report.strategy);
+        assert!(matches!(
+            report.failures.as_slice(),
+            [
+                (
+                    SearchStrategy::PltRelocation,
+                    error::Error::NoGotEntryForFunction(_)
+                ),
+                (
+                    SearchStrategy::GlobDatRelocation,
+                    error::Error::NoGotEntryForFunction(_)
+                ),
+                (
+                    SearchStrategy::OnDiskRelocation,
+                    error::Error::ModuleHasNoFile(_)
+                ),
+            ]
+        ));
+        assert_eq!(CALLBACK, image.read_got_entry("gothook_stripped"));
+
+        hook.unhook().unwrap();
+        assert_eq!(getpid, image.read_got_entry("gothook_stripped"));
+    }
+
+    #[test]
+    fn doesnt_scan_relro_data_preceding_the_got() {
+        let image = ElfBuilder::new(elf::EM_AARCH64)
+            .import("read")
+            .import_data("environ")
+            .build()
+            .map();
+        let getpid = GotHook::find_global_symbol("getpid").unwrap();
+
+        // A function pointer in `.data.rel.ro`, right before the GOT.
+        let data_slot = image.got_entry("environ") - 8;
+        unsafe { ptr::write_volatile(data_slot as *mut u64, getpid) };
+
+        assert!(matches!(
+            GotHook::resolve_got_entry(image.base_address(), "getpid", SearchStrategy::GotScan),
+            Err(error::Error::NoGotEntryForFunction(_))
+        ));
+
+        // The GOT itself is searched.
+        image.write_got_entry("environ", getpid);
+        let target =
+            GotHook::resolve_got_entry(image.base_address(), "getpid", SearchStrategy::GotScan)
+                .unwrap();
+        assert_eq!(image.got_entry("environ"), target.got_entry);
+    }
+
+    #[test]
+    fn doesnt_scan_data_following_the_got() {
+        let image = ElfBuilder::new(elf::EM_AARCH64)
+            .import("read")
+            .build()
+            .map();
+        let getpid = GotHook::find_global_symbol("getpid").unwrap();
+
+        // A function pointer in `.data`, past the PLT's GOT.
+        let data_slot = image.base_address() + image.size() - 8;
+        unsafe { ptr::write_volatile(data_slot as *mut u64, getpid) };
+
+        assert!(matches!(
+            GotHook::resolve_got_entry(image.base_address(), "getpid", SearchStrategy::GotScan),
+            Err(error::Error::NoGotEntryForFunction(_))
+        ));
+    }
+
+    #[test]
+    fn rejects_images_with_multiple_dynamic_segments() {
+        let image = ElfBuilder::new(elf::EM_AARCH64)
+            .dynamic_segments(2)
+            .import("read")
+            .build()
+            .map();
+
+        assert!(matches!(
+            GotHook::new_with_base(image.base_address(), "read", CALLBACK as *const ()),
+            Err(error::Error::MultipleDynamicSegments(2))
+        ));
+    }
+
+    #[test]
+    fn hooks_powerpc64_elfv2_images() {
+        let image = ElfBuilder::new(elf::EM_PPC64)
+            .flags(2)
+            .import("read")
+            .build()
+            .map();
+        image.write_got_entry("read", ORIGINAL);
+
+        let hook =
+            GotHook::new_with_base(image.base_address(), "read", CALLBACK as *const ()).unwrap();
+        assert_eq!(Some(elf::R_PPC64_JMP_SLOT), hook.state().relocation_type);
+        assert_eq!(CALLBACK, image.read_got_entry("read"));
+
+        hook.unhook().unwrap();
+        assert_eq!(ORIGINAL, image.read_got_entry("read"));
+    }
+
+    #[test]
+    fn hooks_big_endian_s390x_images() {
+        let image = ElfBuilder::new(elf::EM_S390)
+            .big_endian()
+            .import("read")
+            .import_data("environ")
+            .build()
+            .map();
+        image.write_got_entry("read", ORIGINAL);
+
+        let hook =
+            GotHook::new_with_base(image.base_address(), "read", CALLBACK as *const ()).unwrap();
+        assert_eq!(Some(elf::R_390_JMP_SLOT), hook.state().relocation_type);
+        assert_eq!(
+            Some(image.got_entry("read") - image.base_address()),
+            hook.relocation_offset()
+        );
+        assert_eq!(CALLBACK, image.read_got_entry("read"));
+
+        hook.unhook().unwrap();
+        assert_eq!(ORIGINAL, image.read_got_entry("read"));
+    }
+
+    #[test]
+    fn reports_the_relocation_offset_readelf_lists() {
+        let _lock = test_elf::lock_loaded_modules();
+
+        let image = ElfBuilder::new(elf::EM_AARCH64)
+            .sections()
+            .import("read")
+            .import("write")
+            .build()
+            .map_file("libgothook-readelf.so");
+
+        let Ok(output) = process::Command::new("readelf")
+            .args(["--relocs", "--wide"])
+            .arg(image.path())
+            .output()
+        else {
+            // readelf isn't installed.
+            return;
+        };
+        assert!(output.status.success());
+
+        // The offset is the first column of the symbol's relocation line.
+        let relocations = String::from_utf8(output.stdout).unwrap();
+        let readelf_offset = relocations
+            .lines()
+            .find(|line| line.contains("R_AARCH64_JUMP_SL") && line.contains(" write"))
+            .and_then(|line| line.split_whitespace().next())
+            .map(|offset| u64::from_str_radix(offset, 16).unwrap());
+
+        let hook =
+            GotHook::new_with_base(image.base_address(), "write", CALLBACK as *const ()).unwrap();
+        assert_eq!(
+            Some(image.got_entry("write") - image.base_address()),
+            hook.relocation_offset()
+        );
+        assert_eq!(readelf_offset, hook.relocation_offset());
+        assert_eq!(Some(0), hook.relocation_addend());
+
+        hook.unhook().unwrap();
+    }
+
+    #[test]
+    fn rejects_powerpc64_elfv1_images() {
+        let image = ElfBuilder::new(elf::EM_PPC64)
+            .flags(1)
+            .import("read")
+            .build()
+            .map();
+
+        assert!(matches!(
+            GotHook::new_with_base(image.base_address(), "read", CALLBACK as *const ()),
+            Err(error::Error::UnsupportedPowerPc64Abi(1))
+        ));
+    }
+
+    #[test]
+    fn hooks_anonymously_mapped_images() {
+        // As if a JIT generated the image, no file backs it.
+        let image = ElfBuilder::new(elf::EM_AARCH64)
+            .import("read")
+            .import("write")
+            .build()
+            .map();
+        image.write_got_entry("write", ORIGINAL);
+
+        let hook =
+            GotHook::new_with_base(image.base_address(), "write", CALLBACK as *const ()).unwrap();
+        assert_eq!(None, hook.state().module_path);
+        assert_eq!(ORIGINAL, hook.get_original_function() as u64);
+        assert_eq!(CALLBACK, image.read_got_entry("write"));
+
+        hook.unhook().unwrap();
+        assert_eq!(ORIGINAL, image.read_got_entry("write"));
+    }
+
+    #[test]
+    fn rejects_images_linked_at_an_address() {
+        let image = ElfBuilder::new(elf::EM_AARCH64)
+            .link_address(0x40_0000)
+            .import("read")
+            .build()
+            .map();
+
+        assert!(matches!(
+            GotHook::new_with_base(image.base_address(), "read", CALLBACK as *const ()),
+            Err(error::Error::UnsupportedElfLinkAddress(0x40_0000))
+        ));
+    }
+
+    #[test]
+    fn adopts_the_primed_function_in_its_mirror() {
+        const RESOLVER: u64 = 0x3000;
+        static MIRROR: AtomicU64 = AtomicU64::new(0);
+
+        let image = ElfBuilder::new(elf::EM_AARCH64)
+            .import("read")
+            .build()
+            .map();
+        image.write_got_entry("read", RESOLVER);
+
+        let mut hook =
+            GotHook::new_with_base(image.base_address(), "read", CALLBACK as *const ()).unwrap();
+        hook.mirror_original_function(&MIRROR);
+        assert_eq!(RESOLVER, MIRROR.load(Ordering::Acquire));
+
+        // As the dynamic linker's resolution stub, write the resolved function.
+        hook.prime_original(|original| {
+            assert_eq!(RESOLVER, original as u64);
+            image.write_got_entry("read", ORIGINAL);
+        })
+        .unwrap();
+        assert_eq!(ORIGINAL, hook.get_original_function() as u64);
+        assert_eq!(ORIGINAL, MIRROR.load(Ordering::Acquire));
+        assert_eq!(CALLBACK, image.read_got_entry("read"));
+
+        // Priming an already resolved function changes nothing.
+        hook.prime_original(|_| {}).unwrap();
+        assert_eq!(ORIGINAL, MIRROR.load(Ordering::Acquire));
+        assert_eq!(CALLBACK, image.read_got_entry("read"));
+
+        hook.unhook().unwrap();
+        assert_eq!(ORIGINAL, image.read_got_entry("read"));
+    }
+
+    #[test]
+    fn returns_the_original_function_ready_to_call() {
+        extern "C" fn callback() -> libc::pid_t {
+            4242
+        }
+
+        let image = ElfBuilder::new(elf::EM_AARCH64)
+            .import("getpid")
+            .build()
+            .map();
+        let getpid = GotHook::find_global_symbol("getpid").unwrap();
+        image.write_got_entry("getpid", getpid);
+
+        let (hook, original_getpid) =
+            GotHook::new_typed_with(callback as extern "C" fn() -> libc::pid_t, |callback| {
+                GotHook::new_with_base(image.base_address(), "getpid", callback)
+            })
+            .unwrap();
+        assert_eq!(callback as *const () as u64, image.read_got_entry("getpid"));
+        assert_eq!(process::id() as libc::pid_t, original_getpid());
+
+        hook.unhook().unwrap();
+    }
+
+    #[test]
+    fn refuses_typing_null_original_functions() {
+        extern "C" fn callback() -> libc::c_int {
+            0
         }
+
+        // As for an unresolved weak symbol.
+        let image = ElfBuilder::new(elf::EM_AARCH64)
+            .import("gothook_weak")
+            .build()
+            .map();
+
+        assert!(matches!(
+            GotHook::new_typed_with(callback as extern "C" fn() -> libc::c_int, |callback| {
+                GotHook::new_with_base(image.base_address(), "gothook_weak", callback)
+            }),
+            Err(error::Error::NullOriginalFunction(name)) if "gothook_weak" == name
+        ));
+        assert_eq!(0, image.read_got_entry("gothook_weak"));
+    }
+
+    #[test]
+    fn rejects_null_callbacks() {
+        let image = ElfBuilder::new(elf::EM_AARCH64)
+            .import("read")
+            .build()
+            .map();
+        image.write_got_entry("read", ORIGINAL);
+
+        assert!(matches!(
+            GotHook::new_with_base(image.base_address(), "read", ptr::null()),
+            Err(error::Error::NullCallback)
+        ));
+        assert!(matches!(
+            GotHook::new("read", ptr::null()),
+            Err(error::Error::NullCallback)
+        ));
+        assert_eq!(ORIGINAL, image.read_got_entry("read"));
+    }
+
+    #[test]
+    fn describes_the_installed_hook() {
+        let _lock = test_elf::lock_reprotects();
+
+        let image = ElfBuilder::new(elf::EM_AARCH64)
+            .import("read")
+            .import("write")
+            .build()
+            .map();
+        image.write_got_entry("write", ORIGINAL);
+
+        // As after RELRO.
+        Protection::ReadOnly
+            .apply(image.got_entry("write"))
+            .unwrap();
+
+        let hook =
+            GotHook::new_with_base(image.base_address(), "write", CALLBACK as *const ()).unwrap();
+        assert_eq!(
+            HookState {
+                function_name: String::from("write"),
+                module_path: None,
+                got_entry: image.got_entry("write"),
+                original_function: ORIGINAL,
+                callback: CALLBACK,
+                relocation_type: Some(elf::R_AARCH64_JUMP_SLOT),
+                relocation_index: Some(1),
+                relocation_offset: Some(image.got_entry("write") - image.base_address()),
+                active: true,
+                page_protection_modified: true,
+            },
+            hook.state()
+        );
+
+        // Hooked on top of us.
+        image.write_got_entry("write", OTHER_CALLBACK);
+        assert!(!hook.state().active);
+    }
+
+    #[test]
+    fn resolves_the_callback_to_the_test_binary() {
+        extern "C" fn callback() {}
+
+        let image = ElfBuilder::new(elf::EM_AARCH64)
+            .import("read")
+            .build()
+            .map();
+
+        let hook =
+            GotHook::new_with_base(image.base_address(), "read", callback as *const ()).unwrap();
+        let info = hook.callback_dl_info().unwrap();
+
+        let test_binary = env::current_exe().unwrap();
+        assert_eq!(
+            test_binary.file_name(),
+            Path::new(&info.module_name).file_name()
+        );
+        assert_eq!(
+            GotHook::find_main_executable_base().unwrap(),
+            info.module_base
+        );
+    }
+
+    #[test]
+    fn keeps_reprotected_hooks_installed() {
+        static CALLS: AtomicU64 = AtomicU64::new(0);
+        extern "C" fn callback() -> u64 {
+            CALLS.fetch_add(1, Ordering::SeqCst) + 1
+        }
+
+        let image = ElfBuilder::new(elf::EM_AARCH64)
+            .import("read")
+            .build()
+            .map();
+        image.write_got_entry("read", ORIGINAL);
+
+        let mut hook =
+            GotHook::new_with_base(image.base_address(), "read", callback as *const ()).unwrap();
+        hook.reprotect(Protection::ReadOnly).unwrap();
+        assert_eq!(
+            Protection::ReadOnly,
+            Protection::of_address(image.got_entry("read")).unwrap()
+        );
+        assert!(hook.state().active);
+        assert!(hook.state().page_protection_modified);
+
+        // Calls through the GOT entry still reach the callback.
+        let function =
+            unsafe { mem::transmute::<u64, extern "C" fn() -> u64>(image.read_got_entry("read")) };
+        assert_eq!(1, function());
+
+        // Unhooking makes the page writable again, as it was.
+        hook.unhook().unwrap();
+        assert_eq!(ORIGINAL, image.read_got_entry("read"));
+        assert_eq!(
+            Protection::ReadWrite,
+            Protection::of_address(image.got_entry("read")).unwrap()
+        );
+    }
+
+    #[test]
+    fn describes_hooks_of_writable_pages_as_unmodified() {
+        let image = ElfBuilder::new(elf::EM_AARCH64)
+            .import("read")
+            .build()
+            .map();
+
+        let hook =
+            GotHook::new_with_base(image.base_address(), "read", CALLBACK as *const ()).unwrap();
+        assert!(!hook.state().page_protection_modified);
+    }
+
+    #[test]
+    fn retries_failed_got_writes_once() {
+        let mut got_entry = ORIGINAL;
+        let got_entry_address = &mut got_entry as *mut u64 as u64;
+
+        // The first write is lost, e.g. to a racing watchpoint.
+        let mut writes = 0;
+        GotHook::write_got_entry_with(got_entry_address, CALLBACK as *const (), |p, f| {
+            writes += 1;
+            if 1 < writes {
+                unsafe { ptr::write_volatile(p, f) };
+            }
+        })
+        .unwrap();
+        assert_eq!(2, writes);
+        assert_eq!(CALLBACK, got_entry);
+
+        // Every write is lost.
+        let mut writes = 0;
+        assert!(matches!(
+            GotHook::write_got_entry_with(got_entry_address, OTHER_CALLBACK as *const (), |_, _| {
+                writes += 1
+            }),
+            Err(error::Error::GotWriteVerificationFailed(a)) if a == got_entry_address
+        ));
+        assert_eq!(2, writes);
+        assert_eq!(CALLBACK, got_entry);
     }
 }
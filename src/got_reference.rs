@@ -0,0 +1,190 @@
+use std::collections::HashMap;
+
+use object::elf;
+use object::read::elf::{FileHeader, ProgramHeader};
+use yaxpeax_arch::{Decoder, U8Reader};
+use yaxpeax_arm::armv8::a64::{InstDecoder, Instruction, Opcode, Operand};
+
+use crate::elf_image::ElfImage;
+use crate::error;
+use crate::machine::Machine;
+use crate::relocations::RelocationSource;
+
+const PAGE_SIZE: u64 = 4096;
+const INSTRUCTION_SIZE: usize = 4;
+
+/// A PLT stub that calls through a GOT entry.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GotReference {
+    /// The address of the GOT entry.
+    pub got_entry: u64,
+
+    /// The name of the function the GOT entry is relocated to.
+    pub function_name: String,
+
+    /// The address of the PLT stub loading the GOT entry.
+    pub plt_stub: u64,
+}
+
+pub(crate) fn find_got_references(base_address: u64) -> error::Result<Vec<GotReference>> {
+    // Find the ELF in memory.
+    let image = ElfImage::find(base_address)?;
+    let elf_endian = image.endian;
+
+    // Only AArch64 PLT stubs are analyzed.
+    let elf_machine = image.machine;
+    if Machine::Aarch64 != elf_machine {
+        return Err(error::Error::UnsupportedElfMachine(
+            image.header.e_machine(elf_endian),
+        ));
+    }
+
+    // Collect the GOT entries of the ELF's jump slots.
+    let mut jump_slots = HashMap::new();
+    let mut relocations = image.relocations()?;
+    while let Some(relocation) = relocations.next() {
+        if RelocationSource::Plt != relocation.source
+            || elf_machine.jump_slot_type() != relocation.reloc_type
+        {
+            continue;
+        }
+
+        if let Some(symbol_name) = relocations.symbol_name(&relocation)? {
+            jump_slots.insert(relocation.got_address, symbol_name);
+        }
+    }
+
+    // Search the ELF's executable segments for PLT stubs loading them.
+    let mut references = Vec::new();
+    for segment in image
+        .segments()?
+        .iter()
+        .filter(|s| elf::PT_LOAD == s.p_type(elf_endian) && 0 != s.p_flags(elf_endian) & elf::PF_X)
+    {
+        let start = segment.p_vaddr(elf_endian) as usize;
+        let end = start + segment.p_filesz(elf_endian) as usize;
+        let Some(code) = image.data.get(start..end) else {
+            continue;
+        };
+
+        for (index, window) in code
+            .windows(INSTRUCTION_SIZE * 2)
+            .enumerate()
+            .step_by(INSTRUCTION_SIZE)
+        {
+            let address = base_address + (start + index) as u64;
+            let Some(got_entry) = find_loaded_got_entry(address, window) else {
+                continue;
+            };
+
+            if let Some(&function_name) = jump_slots.get(&got_entry) {
+                references.push(GotReference {
+                    got_entry,
+                    function_name: String::from(function_name),
+                    plt_stub: address,
+                });
+            }
+        }
+    }
+
+    Ok(references)
+}
+
+fn find_loaded_got_entry(address: u64, code: &[u8]) -> Option<u64> {
+    // PLT stubs start with:
+    //     adrp x16, <GOT entry page>
+    //     ldr  x17, [x16, <GOT entry page offset>]
+    let adrp = decode(&code[..INSTRUCTION_SIZE])?;
+    let (Opcode::ADRP, Operand::Register(_, page_register), Operand::PCOffset(page_offset)) =
+        (adrp.opcode, adrp.operands[0], adrp.operands[1])
+    else {
+        return None;
+    };
+
+    let ldr = decode(&code[INSTRUCTION_SIZE..])?;
+    let (Opcode::LDR, Operand::Register(..), Operand::RegPreIndex(base_register, offset, false)) =
+        (ldr.opcode, ldr.operands[0], ldr.operands[1])
+    else {
+        return None;
+    };
+    if base_register != page_register {
+        return None;
+    }
+
+    let page = (address & !(PAGE_SIZE - 1)).wrapping_add(page_offset as u64);
+    Some(page.wrapping_add(offset as u64))
+}
+
+fn decode(code: &[u8]) -> Option<Instruction> {
+    InstDecoder::default().decode(&mut U8Reader::new(code)).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_elf::{ElfBuilder, TestElf};
+
+    const STUB_SIZE: u64 = 16;
+
+    fn build(stubs: &[u8]) -> TestElf {
+        ElfBuilder::new(elf::EM_AARCH64)
+            .import("read")
+            .import("write")
+            .code(stubs)
+            .build()
+    }
+
+    // The stub the linker emits for each jump slot:
+    //     adrp x16, <GOT entry page>
+    //     ldr  x17, [x16, <GOT entry page offset>]
+    //     add  x16, x16, <GOT entry page offset>
+    //     br   x17
+    fn plt_stub(address: u64, got_entry: u64) -> Vec<u8> {
+        let pages = ((got_entry & !(PAGE_SIZE - 1)) - (address & !(PAGE_SIZE - 1))) >> 12;
+        let page_offset = got_entry & (PAGE_SIZE - 1);
+
+        let adrp = 0x9000_0010 | ((pages as u32 & 0b11) << 29) | ((pages as u32 >> 2) << 5);
+        let ldr = 0xf940_0211 | ((page_offset as u32 / 8) << 10);
+        let add = 0x9100_0210 | ((page_offset as u32) << 10);
+        let br = 0xd61f_0220u32;
+
+        [adrp, ldr, add, br]
+            .iter()
+            .flat_map(|i| i.to_le_bytes())
+            .collect()
+    }
+
+    #[test]
+    fn maps_imports_to_their_plt_stubs() {
+        // The stubs' size, not their contents, determines the layout.
+        let layout = build(&[0; 2 * STUB_SIZE as usize]);
+        let stubs: Vec<u8> = ["read", "write"]
+            .iter()
+            .enumerate()
+            .flat_map(|(index, name)| {
+                plt_stub(
+                    layout.code() + index as u64 * STUB_SIZE,
+                    layout.got_entry(name),
+                )
+            })
+            .collect();
+        let image = build(&stubs).map();
+
+        let code = image.base_address() + layout.code();
+        assert_eq!(
+            find_got_references(image.base_address()).unwrap(),
+            [
+                GotReference {
+                    got_entry: image.got_entry("read"),
+                    function_name: String::from("read"),
+                    plt_stub: code,
+                },
+                GotReference {
+                    got_entry: image.got_entry("write"),
+                    function_name: String::from("write"),
+                    plt_stub: code + STUB_SIZE,
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,80 @@
+use crate::error;
+use crate::{GotHook, HookSpec};
+
+/// A set of hooks that were installed together, and are removed together (in
+/// reverse installation order) when the set is dropped.
+pub struct HookSet {
+    hooks: Vec<GotHook>,
+}
+
+impl HookSet {
+    /// Installs every hook in `specs`, in order.
+    ///
+    /// If any of them fails to install, the hooks installed before it are
+    /// removed before its error is returned.
+    pub fn install_all_or_nothing(specs: &[HookSpec]) -> error::Result<Self> {
+        Self::install_all_or_nothing_with(specs, HookSpec::install)
+    }
+
+    fn install_all_or_nothing_with<T, F: FnMut(T) -> error::Result<GotHook>>(
+        items: impl IntoIterator<Item = T>,
+        mut install: F,
+    ) -> error::Result<Self> {
+        let mut hook_set = Self { hooks: Vec::new() };
+
+        for item in items {
+            // Dropping the partially-installed set rolls it back.
+            hook_set.hooks.push(install(item)?);
+        }
+
+        Ok(hook_set)
+    }
+
+    pub(crate) fn from_hooks(hooks: Vec<GotHook>) -> Self {
+        Self { hooks }
+    }
+
+    pub fn hooks(&self) -> &[GotHook] {
+        &self.hooks
+    }
+}
+
+impl Drop for HookSet {
+    fn drop(&mut self) {
+        // Remove the hooks in reverse order, so that hooks installed on top of
+        // each other restore the right original functions.
+        while let Some(hook) = self.hooks.pop() {
+            drop(hook);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use object::elf;
+
+    use super::*;
+    use crate::test_elf::ElfBuilder;
+
+    #[test]
+    fn rolls_back_when_a_hook_fails() {
+        let image = ElfBuilder::new(elf::EM_AARCH64)
+            .import("read")
+            .import("write")
+            .build()
+            .map();
+        image.write_got_entry("read", 0x1000);
+        image.write_got_entry("write", 0x2000);
+
+        let result = HookSet::install_all_or_nothing_with(["read", "write", "open"], |name| {
+            GotHook::new_with_base(image.base_address(), name, 0x3000 as *const ())
+        });
+
+        assert!(matches!(
+            result,
+            Err(error::Error::NoGotEntryForFunction(name)) if name == "open"
+        ));
+        assert_eq!(0x1000, image.read_got_entry("read"));
+        assert_eq!(0x2000, image.read_got_entry("write"));
+    }
+}
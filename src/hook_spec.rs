@@ -0,0 +1,87 @@
+use std::env;
+
+use crate::error;
+use crate::{GotHook, HookSet};
+
+const HOOK_SPECS_ENVIRONMENT_VARIABLE: &str = "GOTHOOK_HOOKS";
+
+/// A declarative description of a hook: `function_name` in the main
+/// executable is hooked with `callback_symbol` exported by `callback_module`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HookSpec {
+    function_name: String,
+    callback_module: String,
+    callback_symbol: String,
+}
+
+impl HookSpec {
+    pub fn new(function_name: &str, callback_module: &str, callback_symbol: &str) -> Self {
+        Self {
+            function_name: String::from(function_name),
+            callback_module: String::from(callback_module),
+            callback_symbol: String::from(callback_symbol),
+        }
+    }
+
+    pub fn function_name(&self) -> &str {
+        &self.function_name
+    }
+
+    pub fn callback_module(&self) -> &str {
+        &self.callback_module
+    }
+
+    pub fn callback_symbol(&self) -> &str {
+        &self.callback_symbol
+    }
+
+    pub fn install(&self) -> error::Result<GotHook> {
+        GotHook::new_callback_symbol(
+            &self.function_name,
+            &self.callback_module,
+            &self.callback_symbol,
+        )
+    }
+
+    /// Records the hooked function names in the environment, so that a
+    /// re-exec'd process can reinstall the hooks with
+    /// [`HookSpec::reinstall_from_env`].
+    ///
+    /// Modifying the environment isn't thread-safe: this must be called while
+    /// the process is still single-threaded (e.g. from a constructor). Pass
+    /// [`HookSpec::env_var`] to [`Command::env`](std::process::Command::env)
+    /// otherwise.
+    pub fn persist_to_env(specs: &[HookSpec]) {
+        let (name, value) = Self::env_var(specs);
+        env::set_var(name, value);
+    }
+
+    /// Returns the environment variable recording the hooked function names,
+    /// as its name and value.
+    pub fn env_var(specs: &[HookSpec]) -> (&'static str, String) {
+        let function_names: Vec<&str> = specs.iter().map(|s| s.function_name()).collect();
+        (HOOK_SPECS_ENVIRONMENT_VARIABLE, function_names.join(","))
+    }
+
+    /// Returns the specs among `specs` whose function names were recorded in
+    /// the environment by [`HookSpec::persist_to_env`].
+    ///
+    /// The environment is controlled by whoever executes us, so it only ever
+    /// selects among `specs`: the callbacks always come from `specs`, and
+    /// unknown function names are ignored.
+    pub fn from_env(specs: &[HookSpec]) -> Vec<HookSpec> {
+        let function_names = env::var(HOOK_SPECS_ENVIRONMENT_VARIABLE).unwrap_or_default();
+        let function_names: Vec<&str> = function_names.split(',').collect();
+
+        specs
+            .iter()
+            .filter(|s| function_names.contains(&s.function_name()))
+            .cloned()
+            .collect()
+    }
+
+    /// Installs the hooks [`HookSpec::from_env`] selects among `specs`.
+    pub fn reinstall_from_env(specs: &[HookSpec]) -> error::Result<HookSet> {
+        HookSet::install_all_or_nothing(&Self::from_env(specs))
+    }
+}
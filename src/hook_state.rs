@@ -0,0 +1,35 @@
+/// An owned snapshot of a [`GotHook`](crate::GotHook)'s state.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HookState {
+    /// The hooked function's name.
+    pub function_name: String,
+
+    /// The path of the module whose GOT was hooked, if it has one.
+    pub module_path: Option<String>,
+
+    /// The address of the hooked GOT entry.
+    pub got_entry: u64,
+
+    /// The function the GOT entry pointed to before it was hooked.
+    pub original_function: u64,
+
+    /// The function the GOT entry was hooked with.
+    pub callback: u64,
+
+    /// The type of the relocation the GOT entry was located through, if any.
+    pub relocation_type: Option<u32>,
+
+    /// The index of that relocation within its relocation table.
+    pub relocation_index: Option<usize>,
+
+    /// The raw `r_offset` of that relocation.
+    pub relocation_offset: Option<u64>,
+
+    /// Whether the GOT entry still points to the callback, i.e. wasn't hooked
+    /// on top of us.
+    pub active: bool,
+
+    /// Whether the GOT entry's page currently has a different protection than
+    /// it had before it was hooked.
+    pub page_protection_modified: bool,
+}
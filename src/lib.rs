@@ -1,4 +1,53 @@
-mod error;
+pub mod analyze;
+mod callback_info;
+mod deferred_reprotect;
+mod dynamic_segment;
+mod dynamic_symbols;
+mod elf_image;
+mod errno;
+mod errno_hook;
+pub mod error;
+mod fn_ptr;
+mod global_hook;
 mod got_hook;
+#[cfg(feature = "got-references")]
+mod got_reference;
+mod hook_set;
+mod hook_spec;
+mod hook_state;
+mod libc_functions;
+mod machine;
+mod module_hooker;
+mod module_info;
+mod object_relocations;
+mod on_disk_relocations;
+mod protection;
+mod registry;
+mod relocations;
+mod resolved_target;
+mod sandbox;
+mod search_strategy;
+mod telemetry;
+#[cfg(test)]
+mod test_elf;
+mod trace;
 
+pub use callback_info::CallbackInfo;
+pub use deferred_reprotect::{defer_reprotects, flush_pending_reprotects};
+pub use errno_hook::{ErrnoCallback, OpenCallback, OpenatCallback, SendCallback, WriteCallback};
+pub use fn_ptr::FnPtr;
+pub use global_hook::{GlobalHook, ModuleScan, ScanOutcome};
 pub use got_hook::GotHook;
+#[cfg(feature = "got-references")]
+pub use got_reference::GotReference;
+pub use hook_set::HookSet;
+pub use hook_spec::HookSpec;
+pub use hook_state::HookState;
+pub use libc_functions::{OpenFunction, OpenatFunction, SendFunction, WriteFunction};
+pub use module_hooker::ModuleHooker;
+pub use module_info::{loaded_modules, ModuleInfo};
+pub use protection::Protection;
+pub use registry::is_hooked;
+pub use sandbox::{sandbox_filesystem, FsAccess, FsAction, FsPolicy, FsSandbox};
+pub use search_strategy::{SearchReport, SearchStrategy};
+pub use trace::{set_trace_capture_limit, take_trace_records, TraceRecord};
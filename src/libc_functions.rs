@@ -0,0 +1,44 @@
+use std::ffi::{c_char, c_int, c_uint, c_void};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use libc::{loff_t, mode_t, off_t, size_t, ssize_t};
+
+use crate::GotHook;
+
+// The prototypes of the libc functions the crate's callbacks forward to.
+pub type OpenFunction = unsafe extern "C" fn(*const c_char, c_int, mode_t) -> c_int;
+pub type OpenatFunction = unsafe extern "C" fn(c_int, *const c_char, c_int, mode_t) -> c_int;
+pub type WriteFunction = unsafe extern "C" fn(c_int, *const c_void, size_t) -> ssize_t;
+pub type SendFunction = unsafe extern "C" fn(c_int, *const c_void, size_t, c_int) -> ssize_t;
+pub(crate) type SendfileFunction =
+    unsafe extern "C" fn(c_int, c_int, *mut off_t, size_t) -> ssize_t;
+pub(crate) type CopyFileRangeFunction =
+    unsafe extern "C" fn(c_int, *mut loff_t, c_int, *mut loff_t, size_t, c_uint) -> ssize_t;
+pub(crate) type PathModeFunction = unsafe extern "C" fn(*const c_char, mode_t) -> c_int;
+pub(crate) type StatFunction = unsafe extern "C" fn(*const c_char, *mut c_void) -> c_int;
+pub(crate) type FstatatFunction =
+    unsafe extern "C" fn(c_int, *const c_char, *mut c_void, c_int) -> c_int;
+pub(crate) type StatxFunction =
+    unsafe extern "C" fn(c_int, *const c_char, c_int, c_uint, *mut c_void) -> c_int;
+pub(crate) type AccessFunction = unsafe extern "C" fn(*const c_char, c_int) -> c_int;
+pub(crate) type MkdiratFunction = unsafe extern "C" fn(c_int, *const c_char, mode_t) -> c_int;
+pub(crate) type PathFunction = unsafe extern "C" fn(*const c_char) -> c_int;
+pub(crate) type UnlinkatFunction = unsafe extern "C" fn(c_int, *const c_char, c_int) -> c_int;
+pub(crate) type RenameFunction = unsafe extern "C" fn(*const c_char, *const c_char) -> c_int;
+pub(crate) type RenameatFunction =
+    unsafe extern "C" fn(c_int, *const c_char, c_int, *const c_char) -> c_int;
+pub(crate) type Renameat2Function =
+    unsafe extern "C" fn(c_int, *const c_char, c_int, *const c_char, c_uint) -> c_int;
+
+/// Returns the function a callback forwards `function_name` to, or null if
+/// it can't be found.
+pub(crate) fn get_original_function(
+    function_name: &str,
+    original_function: &AtomicU64,
+) -> *const () {
+    match original_function.load(Ordering::Acquire) {
+        // We were called before the hook recorded the original function.
+        0 => GotHook::find_global_symbol(function_name).unwrap_or(0) as *const (),
+        function => function as *const (),
+    }
+}
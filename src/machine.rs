@@ -0,0 +1,90 @@
+use object::elf;
+use object::read::elf::FileHeader;
+
+use crate::error;
+
+// The ELFv2 ABI version in the PowerPC64 `e_flags`.
+const EF_PPC64_ABI_ELFV2: u32 = 2;
+
+/// The architectures whose GOT can be hooked.
+///
+/// PowerPC64 is supported for the ELFv2 ABI only. There, a jump slot holds
+/// the function's global entry point, which derives the callee's TOC pointer
+/// (`r2`) from `r12`, while the caller's PLT call stub saves and restores its
+/// own TOC pointer around the call. The callback must therefore be a global
+/// entry point (which any Rust or C function pointer is), and only calls that
+/// go through the PLT call stubs are hooked: calls the linker resolved to a
+/// function's local entry point never touch the GOT.
+///
+/// s390x is supported for 64-bit ELFs only (not 31-bit s390). It's big-endian,
+/// which needs no special handling since GOT entries are read and written in
+/// the native byte order.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Machine {
+    Aarch64,
+    PowerPc64,
+    S390x,
+}
+
+impl Machine {
+    pub(crate) fn from_elf_header<Elf: FileHeader>(
+        header: &Elf,
+        endian: Elf::Endian,
+    ) -> error::Result<Self> {
+        match header.e_machine(endian) {
+            elf::EM_AARCH64 => Ok(Machine::Aarch64),
+            elf::EM_PPC64 => {
+                // ELFv1 jump slots hold function descriptors, not addresses.
+                let abi = header.e_flags(endian) & elf::EF_PPC64_ABI;
+                if EF_PPC64_ABI_ELFV2 != abi {
+                    return Err(error::Error::UnsupportedPowerPc64Abi(abi));
+                }
+
+                Ok(Machine::PowerPc64)
+            }
+            elf::EM_S390 => Ok(Machine::S390x),
+            machine => Err(error::Error::UnsupportedElfMachine(machine)),
+        }
+    }
+
+    pub(crate) fn jump_slot_type(self) -> u32 {
+        match self {
+            Machine::Aarch64 => elf::R_AARCH64_JUMP_SLOT,
+            Machine::PowerPc64 => elf::R_PPC64_JMP_SLOT,
+            Machine::S390x => elf::R_390_JMP_SLOT,
+        }
+    }
+
+    pub(crate) fn glob_dat_type(self) -> u32 {
+        match self {
+            Machine::Aarch64 => elf::R_AARCH64_GLOB_DAT,
+            Machine::PowerPc64 => elf::R_PPC64_GLOB_DAT,
+            Machine::S390x => elf::R_390_GLOB_DAT,
+        }
+    }
+
+    pub(crate) fn irelative_type(self) -> u32 {
+        match self {
+            Machine::Aarch64 => elf::R_AARCH64_IRELATIVE,
+            Machine::PowerPc64 => elf::R_PPC64_IRELATIVE,
+            Machine::S390x => elf::R_390_IRELATIVE,
+        }
+    }
+
+    /// Returns the number of entries the dynamic linker reserves at the start
+    /// of the PLT's GOT (`DT_PLTGOT`), before the jump slots.
+    pub(crate) fn reserved_plt_got_entries(self) -> u64 {
+        match self {
+            Machine::Aarch64 | Machine::S390x => 3,
+            Machine::PowerPc64 => 2,
+        }
+    }
+
+    pub(crate) fn relative_type(self) -> u32 {
+        match self {
+            Machine::Aarch64 => elf::R_AARCH64_RELATIVE,
+            Machine::PowerPc64 => elf::R_PPC64_RELATIVE,
+            Machine::S390x => elf::R_390_RELATIVE,
+        }
+    }
+}
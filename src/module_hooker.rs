@@ -0,0 +1,148 @@
+use object::elf::{self, FileHeader64};
+use object::endian::Endianness;
+use object::read::elf::Sym;
+
+use crate::dynamic_symbols::DynamicSymbols;
+use crate::elf_image::ElfImage;
+use crate::error;
+#[cfg(feature = "got-references")]
+use crate::got_reference::{self, GotReference};
+use crate::module_info;
+use crate::search_strategy::SearchStrategy;
+use crate::GotHook;
+
+/// Hooks functions in a single loaded module.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ModuleHooker {
+    base_address: u64,
+    allow_dynamic_linker: bool,
+}
+
+impl ModuleHooker {
+    /// Targets the loaded module whose path, or file name, is `module`.
+    pub fn new(module: &str) -> error::Result<Self> {
+        let module = module_info::find_loaded_module(module)?;
+        Ok(Self::with_base(module.base_address))
+    }
+
+    /// Targets the ELF image loaded at `base_address`.
+    pub fn with_base(base_address: u64) -> Self {
+        Self {
+            base_address,
+            allow_dynamic_linker: false,
+        }
+    }
+
+    pub fn base_address(&self) -> u64 {
+        self.base_address
+    }
+
+    /// Whether to allow hooking GOT entries of the dynamic linker, which fails
+    /// with [`error::Error::RefusingToHookDynamicLinker`] by default.
+    ///
+    /// Hooking functions the dynamic linker uses while it's resolving
+    /// symbols can deadlock or crash it.
+    pub fn allow_dynamic_linker(mut self, allow: bool) -> Self {
+        self.allow_dynamic_linker = allow;
+        self
+    }
+
+    pub fn hook(&self, function_name: &str, callback: *const ()) -> error::Result<GotHook> {
+        // Locate the function's GOT entry.
+        let target = GotHook::resolve_got_entry(
+            self.base_address,
+            function_name,
+            SearchStrategy::PltRelocation,
+        )?;
+
+        GotHook::install(function_name, target, callback, self.allow_dynamic_linker)
+    }
+
+    /// Returns the value `function_name`'s GOT entry holds when it isn't hooked.
+    ///
+    /// This is only known for functions the module defines itself, whose
+    /// address is the module's base plus the symbol's value. For imported
+    /// functions it depends on how the dynamic linker resolved them, and
+    /// `None` is returned.
+    pub fn expected_got_value(&self, function_name: &str) -> error::Result<Option<u64>> {
+        // Find the ELF in memory.
+        let image = ElfImage::find(self.base_address)?;
+        let elf_endian = image.endian;
+
+        // Find the function's dynamic symbol.
+        let symbol = DynamicSymbols::<FileHeader64<Endianness>>::new(
+            image.data,
+            image.dynamic_segment()?,
+            elf_endian,
+        )?
+        .find(function_name)?
+        .ok_or_else(|| error::Error::NoElfDynamicSymbol(String::from(function_name)))?;
+
+        // Imported functions are resolved by the dynamic linker, and so are
+        // indirect functions, whose resolver picks the implementation at runtime.
+        if symbol.is_undefined(elf_endian) || elf::STT_GNU_IFUNC == symbol.st_type() {
+            return Ok(None);
+        }
+
+        Ok(Some(self.base_address + symbol.st_value(elf_endian)))
+    }
+
+    /// Lists the PLT stubs that call through the module's GOT entries.
+    ///
+    /// Only AArch64 modules are supported.
+    #[cfg(feature = "got-references")]
+    pub fn got_references(&self) -> error::Result<Vec<GotReference>> {
+        got_reference::find_got_references(self.base_address)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_elf::{self, ElfBuilder};
+
+    #[test]
+    fn expects_self_defined_functions_at_their_address() {
+        let image = ElfBuilder::new(elf::EM_AARCH64)
+            .define("gothook_defined", 0x1234)
+            .import("read")
+            .build()
+            .map();
+        let hooker = ModuleHooker::with_base(image.base_address());
+
+        assert_eq!(
+            Some(image.base_address() + 0x1234),
+            hooker.expected_got_value("gothook_defined").unwrap()
+        );
+        assert_eq!(None, hooker.expected_got_value("read").unwrap());
+        assert!(matches!(
+            hooker.expected_got_value("write"),
+            Err(error::Error::NoElfDynamicSymbol(name)) if "write" == name
+        ));
+    }
+
+    #[test]
+    fn refuses_to_hook_the_dynamic_linker_unless_allowed() {
+        let _lock = test_elf::lock_loaded_modules();
+
+        // Named like glibc's dynamic linker.
+        let image = ElfBuilder::new(elf::EM_AARCH64)
+            .import("read")
+            .build()
+            .map_file("ld-linux-aarch64.so.1");
+        let hooker = ModuleHooker::new(image.path().to_str().unwrap()).unwrap();
+
+        assert!(matches!(
+            hooker.hook("read", 0x2000 as *const ()),
+            Err(error::Error::RefusingToHookDynamicLinker(name)) if "read" == name
+        ));
+        assert_eq!(0, image.read_got_entry("read"));
+
+        let hook = hooker
+            .allow_dynamic_linker(true)
+            .hook("read", 0x2000 as *const ())
+            .unwrap();
+        assert_eq!(0x2000, image.read_got_entry("read"));
+        hook.unhook().unwrap();
+    }
+}
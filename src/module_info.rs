@@ -0,0 +1,145 @@
+use std::path::PathBuf;
+use std::slice;
+
+use procfs::process::{MMPermissions, MMapPath, Process};
+
+use crate::error;
+
+const ELF_MAGIC: [u8; 4] = [0x7f, b'E', b'L', b'F'];
+
+// File name prefixes of the dynamic linkers of glibc and musl.
+const DYNAMIC_LINKER_PREFIXES: [&str; 3] = ["ld-linux", "ld64.so", "ld-musl"];
+
+// File names of bionic's dynamic linkers.
+const DYNAMIC_LINKER_NAMES: [&str; 2] = ["linker", "linker64"];
+
+/// A module (executable or shared library) loaded into the current process.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ModuleInfo {
+    /// The path of the file the module was loaded from.
+    pub path: PathBuf,
+
+    /// The address the module is loaded at.
+    pub base_address: u64,
+
+    /// The size of the module's memory range, from `base_address` to the end
+    /// of its last mapping.
+    pub size: u64,
+}
+
+/// Lists the modules loaded into the current process, in address order.
+///
+/// A module is a file-backed mapping of an ELF header, together with the
+/// mappings of the same file that directly follow it.
+pub fn loaded_modules() -> error::Result<Vec<ModuleInfo>> {
+    // Locate the current process in '/proc'.
+    let process = Process::myself().map_err(error::Error::FindCurrentProcess)?;
+
+    let mut modules: Vec<ModuleInfo> = Vec::new();
+    let mut last_path = None;
+
+    for map in process
+        .maps()
+        .map_err(error::Error::ReadProcessMaps)?
+        .iter()
+    {
+        let MMapPath::Path(path) = &map.pathname else {
+            last_path = None;
+            continue;
+        };
+
+        // Extend the current module with the following mappings of its file.
+        if last_path == Some(path) {
+            if let Some(module) = modules.last_mut() {
+                module.size = map.address.1 - module.base_address;
+            }
+            continue;
+        }
+        last_path = None;
+
+        // A new module starts at a readable mapping of an ELF header.
+        if 0 != map.offset || !map.perms.contains(MMPermissions::READ) {
+            continue;
+        }
+
+        let header = unsafe { slice::from_raw_parts(map.address.0 as *const u8, ELF_MAGIC.len()) };
+        if ELF_MAGIC != header {
+            continue;
+        }
+
+        modules.push(ModuleInfo {
+            path: path.clone(),
+            base_address: map.address.0,
+            size: map.address.1 - map.address.0,
+        });
+        last_path = Some(path);
+    }
+
+    Ok(modules)
+}
+
+pub(crate) fn find_loaded_module(module: &str) -> error::Result<ModuleInfo> {
+    loaded_modules()?
+        .into_iter()
+        .find(|m| m.path.as_os_str() == module || m.path.file_name() == Some(module.as_ref()))
+        .ok_or_else(|| error::Error::NoLoadedModule(String::from(module)))
+}
+
+/// Returns whether `address` lies within the dynamic linker's mapping.
+pub(crate) fn is_in_dynamic_linker(address: u64) -> error::Result<bool> {
+    Ok(loaded_modules()?
+        .iter()
+        .find(|m| (m.base_address..m.base_address + m.size).contains(&address))
+        .and_then(|m| m.path.file_name())
+        .map(|n| is_dynamic_linker_name(&n.to_string_lossy()))
+        .unwrap_or(false))
+}
+
+/// Returns whether `file_name` is the file name of a dynamic linker.
+fn is_dynamic_linker_name(file_name: &str) -> bool {
+    DYNAMIC_LINKER_PREFIXES
+        .iter()
+        .any(|p| file_name.starts_with(p))
+        || DYNAMIC_LINKER_NAMES.contains(&file_name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recognizes_dynamic_linker_names() {
+        for name in [
+            "ld-linux-aarch64.so.1",
+            "ld-linux-x86-64.so.2",
+            "ld64.so.2",
+            "ld-musl-aarch64.so.1",
+            "linker",
+            "linker64",
+        ] {
+            assert!(is_dynamic_linker_name(name), "{name}");
+        }
+
+        for name in ["libc.so.6", "liblinker.so", "linkerd", "linker64.so"] {
+            assert!(!is_dynamic_linker_name(name), "{name}");
+        }
+    }
+
+    #[test]
+    fn finds_addresses_in_the_dynamic_linker() {
+        let dynamic_linker = loaded_modules()
+            .unwrap()
+            .into_iter()
+            .find(|m| {
+                m.path
+                    .file_name()
+                    .map(|n| is_dynamic_linker_name(&n.to_string_lossy()))
+                    .unwrap_or(false)
+            })
+            .unwrap();
+        assert!(is_in_dynamic_linker(dynamic_linker.base_address).unwrap());
+
+        let libc = find_loaded_module("libc.so.6").unwrap();
+        assert!(!is_in_dynamic_linker(libc.base_address).unwrap());
+    }
+}
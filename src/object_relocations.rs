@@ -0,0 +1,219 @@
+use std::mem;
+
+use object::elf;
+use object::read::elf::{FileHeader, Rel, Rela};
+use object::{Endian, ReadRef};
+
+use crate::dynamic_segment;
+use crate::dynamic_symbols::DynamicSymbols;
+use crate::error;
+use crate::machine::Machine;
+use crate::relocations::{Relocation, RelocationSource, RelrEntries};
+
+enum RelocationTable<'a, Elf: FileHeader> {
+    Rela(RelocationSource, &'a [Elf::Rela]),
+    Rel(RelocationSource, &'a [Elf::Rel]),
+    Relr(RelrEntries<'a>),
+}
+
+pub(crate) struct Relocations<'a, Elf: FileHeader> {
+    base_address: u64,
+    machine: Machine,
+    endian: Elf::Endian,
+    image_size: u64,
+    plt_size_derived: bool,
+    symbols: DynamicSymbols<'a, Elf>,
+    tables: Vec<RelocationTable<'a, Elf>>,
+    table_index: usize,
+    entry_index: usize,
+}
+
+impl<'a, Elf: FileHeader> Relocations<'a, Elf> {
+    pub(crate) fn new(
+        base_address: u64,
+        machine: Machine,
+        data: &'a [u8],
+        dynamic_segment: &'a [Elf::Dyn],
+        endian: Elf::Endian,
+    ) -> error::Result<Self> {
+        // Read the dynamic symbols the relocations refer to.
+        let symbols = DynamicSymbols::new(data, dynamic_segment, endian)?;
+
+        // Collect every relocation table the dynamic segment references.
+        let mut tables = Vec::new();
+        let plt_size_derived =
+            dynamic_segment::find_dynamic_value::<Elf>(dynamic_segment, endian, elf::DT_PLTRELSZ)
+                .is_none();
+
+        if let Some((address, size)) = dynamic_segment::find_dynamic_table::<Elf>(
+            data,
+            dynamic_segment,
+            endian,
+            elf::DT_JMPREL,
+            elf::DT_PLTRELSZ,
+        ) {
+            // The PLT relocations are either REL or RELA, as specified by `DT_PLTREL`.
+            let is_rela =
+                dynamic_segment::find_dynamic_value::<Elf>(dynamic_segment, endian, elf::DT_PLTREL)
+                    .map(|kind| u64::from(elf::DT_RELA) == kind)
+                    .unwrap_or(Elf::is_type_64_sized());
+
+            tables.push(if is_rela {
+                RelocationTable::Rela(
+                    RelocationSource::Plt,
+                    Self::read_table(data, address, size)
+                        .ok_or(error::Error::ReadElfPltRelocationTable)?,
+                )
+            } else {
+                RelocationTable::Rel(
+                    RelocationSource::Plt,
+                    Self::read_table(data, address, size)
+                        .ok_or(error::Error::ReadElfPltRelocationTable)?,
+                )
+            });
+        }
+
+        if let Some((address, size)) = dynamic_segment::find_dynamic_table::<Elf>(
+            data,
+            dynamic_segment,
+            endian,
+            elf::DT_RELA,
+            elf::DT_RELASZ,
+        ) {
+            tables.push(RelocationTable::Rela(
+                RelocationSource::Rela,
+                Self::read_table(data, address, size)
+                    .ok_or(error::Error::ReadElfRelocationTable)?,
+            ));
+        }
+
+        if let Some((address, size)) = dynamic_segment::find_dynamic_table::<Elf>(
+            data,
+            dynamic_segment,
+            endian,
+            elf::DT_REL,
+            elf::DT_RELSZ,
+        ) {
+            tables.push(RelocationTable::Rel(
+                RelocationSource::Rel,
+                Self::read_table(data, address, size)
+                    .ok_or(error::Error::ReadElfRelocationTable)?,
+            ));
+        }
+
+        if let Some((address, size)) = dynamic_segment::find_dynamic_table::<Elf>(
+            data,
+            dynamic_segment,
+            endian,
+            dynamic_segment::DT_RELR,
+            dynamic_segment::DT_RELRSZ,
+        ) {
+            tables.push(RelocationTable::Relr(RelrEntries::new(
+                data.read_bytes_at(address, size)
+                    .map_err(|_| error::Error::ReadElfRelocationTable)?,
+                Elf::is_type_64_sized(),
+                endian.is_big_endian(),
+            )));
+        }
+
+        Ok(Self {
+            base_address,
+            machine,
+            endian,
+            image_size: data.len() as u64,
+            plt_size_derived,
+            symbols,
+            tables,
+            table_index: 0,
+            entry_index: 0,
+        })
+    }
+
+    fn read_table<T: object::Pod>(data: &'a [u8], address: u64, size: u64) -> Option<&'a [T]> {
+        data.read_slice_at(address, size as usize / mem::size_of::<T>())
+            .ok()
+    }
+
+    pub(crate) fn symbol_name(&self, relocation: &Relocation) -> error::Result<Option<&'a str>> {
+        // Symbol 0 is the undefined symbol, which has no name.
+        if 0 == relocation.symbol_index {
+            return Ok(None);
+        }
+
+        let symbol = self.symbols.symbol(relocation.symbol_index)?;
+        self.symbols.symbol_name(symbol).map(Some)
+    }
+}
+
+impl<'a, Elf: FileHeader> Iterator for Relocations<'a, Elf> {
+    type Item = Relocation;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.table_index < self.tables.len() {
+            let entry_index = self.entry_index;
+
+            let relocation = match &mut self.tables[self.table_index] {
+                &mut RelocationTable::Rela(source, table) => table.get(entry_index).map(|r| {
+                    self.entry_index += 1;
+                    (
+                        source,
+                        r.r_offset(self.endian).into(),
+                        r.r_sym(self.endian, false),
+                        r.r_type(self.endian, false),
+                        Some(r.r_addend(self.endian).into()),
+                    )
+                }),
+                &mut RelocationTable::Rel(source, table) => table.get(entry_index).map(|r| {
+                    self.entry_index += 1;
+                    (
+                        source,
+                        r.r_offset(self.endian).into(),
+                        r.r_sym(self.endian),
+                        r.r_type(self.endian),
+                        None,
+                    )
+                }),
+                RelocationTable::Relr(entries) => {
+                    if let Some((index, offset)) = entries.next() {
+                        return Some(Relocation::relative(
+                            self.base_address,
+                            self.machine,
+                            index,
+                            offset,
+                        ));
+                    }
+                    None
+                }
+            };
+
+            // A PLT relocation table whose size had to be derived may run into
+            // whatever follows it (e.g. code): it ends with its last jump slot.
+            let relocation = relocation.filter(|&(source, offset, _, reloc_type, _)| {
+                RelocationSource::Plt != source
+                    || !self.plt_size_derived
+                    || (offset < self.image_size
+                        && (self.machine.jump_slot_type() == reloc_type
+                            || self.machine.irelative_type() == reloc_type))
+            });
+
+            let Some((source, offset, symbol_index, reloc_type, addend)) = relocation else {
+                // This table is exhausted, move to the next one.
+                self.table_index += 1;
+                self.entry_index = 0;
+                continue;
+            };
+
+            return Some(Relocation {
+                source,
+                got_address: self.base_address + offset,
+                index: entry_index,
+                offset,
+                symbol_index,
+                reloc_type,
+                addend,
+            });
+        }
+
+        None
+    }
+}
@@ -0,0 +1,174 @@
+use std::ffi::c_void;
+use std::fs::File;
+use std::num::NonZeroUsize;
+use std::os::fd::AsRawFd;
+use std::slice;
+use std::str;
+
+use nix::sys::mman::{self, MapFlags, ProtFlags};
+use object::elf::FileHeader64;
+use object::endian::Endianness;
+use object::read::elf::{FileHeader, Rela, SectionHeader, Sym};
+
+use crate::error;
+use crate::machine::Machine;
+use crate::resolved_target::MatchedRelocation;
+
+/// A read-only mapping of a whole file.
+struct MappedFile {
+    address: *mut c_void,
+    size: usize,
+}
+
+impl MappedFile {
+    fn new(path: &str) -> error::Result<Self> {
+        let file = File::open(path)
+            .map_err(|e| error::Error::ReadModuleFile(String::from(path), e.to_string()))?;
+        let size = file
+            .metadata()
+            .map_err(|e| error::Error::ReadModuleFile(String::from(path), e.to_string()))?
+            .len() as usize;
+        let length = NonZeroUsize::new(size).ok_or_else(|| {
+            error::Error::ReadModuleFile(String::from(path), String::from("file is empty"))
+        })?;
+
+        let address = unsafe {
+            mman::mmap(
+                None,
+                length,
+                ProtFlags::PROT_READ,
+                MapFlags::MAP_PRIVATE,
+                file.as_raw_fd(),
+                0,
+            )
+        }
+        .map_err(|e| error::Error::ReadModuleFile(String::from(path), e.to_string()))?;
+
+        Ok(Self { address, size })
+    }
+
+    fn data(&self) -> &[u8] {
+        unsafe { slice::from_raw_parts(self.address as *const u8, self.size) }
+    }
+}
+
+impl Drop for MappedFile {
+    fn drop(&mut self) {
+        let _ = unsafe { mman::munmap(self.address, self.size) };
+    }
+}
+
+/// Locates `function_name`'s GOT entry through the relocation sections of the
+/// module's file, which survive even when the module's in-memory relocation
+/// tables don't.
+pub(crate) fn find_got_entry(
+    base_address: u64,
+    module_path: &str,
+    machine: Machine,
+    function_name: &str,
+) -> error::Result<(u64, MatchedRelocation)> {
+    // Map the module's file.
+    let file = MappedFile::new(module_path)?;
+    let data = file.data();
+
+    // Parse the file's header and sections.
+    let header: &FileHeader64<Endianness> =
+        FileHeader64::parse(data).map_err(error::Error::ParseElfHeader)?;
+    let endian = header.endian().map_err(error::Error::GetElfEndianness)?;
+    let sections = header
+        .sections(endian, data)
+        .map_err(error::Error::ReadElfSections)?;
+
+    // Search the RELA sections (`.rela.plt` and `.rela.dyn`) for the function's relocation.
+    for section in sections.iter() {
+        let Some((relocations, symbol_table_index)) = section
+            .rela(endian, data)
+            .map_err(error::Error::ReadElfSections)?
+        else {
+            continue;
+        };
+        let symbols = sections
+            .symbol_table_by_index(endian, data, symbol_table_index)
+            .map_err(error::Error::ReadElfSections)?;
+
+        for (index, relocation) in relocations.iter().enumerate() {
+            // Skip relocations that don't point a GOT entry at a function.
+            let reloc_type = relocation.r_type(endian, false);
+            if machine.jump_slot_type() != reloc_type && machine.glob_dat_type() != reloc_type {
+                continue;
+            }
+
+            // Skip relocations that aren't the function.
+            let Ok(symbol) = symbols.symbol(relocation.r_sym(endian, false) as usize) else {
+                continue;
+            };
+            let symbol_name = symbol
+                .name(endian, symbols.strings())
+                .map_err(error::Error::FindElfSymbolName)?;
+            if str::from_utf8(symbol_name) != Ok(function_name) {
+                continue;
+            }
+
+            // The file's relocation offsets are relative to the module's base.
+            let offset = relocation.r_offset(endian);
+            return Ok((
+                base_address + offset,
+                MatchedRelocation {
+                    reloc_type,
+                    index,
+                    offset,
+                    addend: Some(relocation.r_addend(endian)),
+                },
+            ));
+        }
+    }
+
+    Err(error::Error::NoGotEntryForFunction(String::from(
+        function_name,
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use object::elf;
+
+    use crate::search_strategy::SearchStrategy;
+    use crate::test_elf::{self, ElfBuilder};
+    use crate::GotHook;
+
+    #[test]
+    fn finds_the_got_entries_found_in_memory() {
+        let _lock = test_elf::lock_loaded_modules();
+
+        let image = ElfBuilder::new(elf::EM_AARCH64)
+            .sections()
+            .import("read")
+            .import("write")
+            .import_data("environ")
+            .build()
+            .map_file("libgothook-on-disk.so");
+
+        for (function_name, strategy) in [
+            ("read", SearchStrategy::PltRelocation),
+            ("write", SearchStrategy::PltRelocation),
+            ("environ", SearchStrategy::GlobDatRelocation),
+        ] {
+            let in_memory =
+                GotHook::resolve_got_entry(image.base_address(), function_name, strategy).unwrap();
+            let on_disk = GotHook::resolve_got_entry(
+                image.base_address(),
+                function_name,
+                SearchStrategy::OnDiskRelocation,
+            )
+            .unwrap();
+
+            assert_eq!(image.got_entry(function_name), on_disk.got_entry);
+            assert_eq!(in_memory.got_entry, on_disk.got_entry);
+
+            let (in_memory, on_disk) = (in_memory.relocation.unwrap(), on_disk.relocation.unwrap());
+            assert_eq!(in_memory.reloc_type, on_disk.reloc_type);
+            assert_eq!(in_memory.index, on_disk.index);
+            assert_eq!(in_memory.offset, on_disk.offset);
+        }
+    }
+}
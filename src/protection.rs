@@ -0,0 +1,82 @@
+use std::ffi::c_void;
+
+use nix::sys::mman::{self, ProtFlags};
+use nix::unistd::{self, SysconfVar};
+use procfs::process::{MMPermissions, Process};
+
+use crate::error;
+
+lazy_static::lazy_static! {
+    // Pages aren't 4K everywhere: PowerPC64 and AArch64 kernels often use
+    // 64K (or, on AArch64, 16K) pages.
+    static ref PAGE_SIZE: u64 = match unistd::sysconf(SysconfVar::PAGE_SIZE) {
+        Ok(Some(page_size)) => page_size as u64,
+        _ => 4096,
+    };
+}
+
+/// The protection of a hooked GOT entry's page.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Protection {
+    /// The page can only be read, as after RELRO.
+    ReadOnly,
+
+    /// The page can be read and written, as while hooking.
+    ReadWrite,
+}
+
+impl Protection {
+    /// Returns the current protection of the page containing `address`.
+    pub(crate) fn of_address(address: u64) -> error::Result<Self> {
+        // Locate the current process in '/proc'.
+        let process = Process::myself().map_err(error::Error::FindCurrentProcess)?;
+
+        let map = process
+            .maps()
+            .map_err(error::Error::ReadProcessMaps)?
+            .into_iter()
+            .find(|m| (m.address.0..m.address.1).contains(&address))
+            .ok_or(error::Error::NoMappingForAddress(address))?;
+
+        Ok(if map.perms.contains(MMPermissions::WRITE) {
+            Protection::ReadWrite
+        } else {
+            Protection::ReadOnly
+        })
+    }
+
+    /// Applies the protection to the page containing `address`.
+    pub(crate) fn apply(self, address: u64) -> error::Result<()> {
+        let page = page_of(address);
+        unsafe {
+            mman::mprotect(page as *mut c_void, *PAGE_SIZE as usize, self.flags())
+                .map_err(|e| error::Error::ModifyMemoryPageProtection(e, page))
+        }
+    }
+
+    fn flags(self) -> ProtFlags {
+        match self {
+            Protection::ReadOnly => ProtFlags::PROT_READ,
+            Protection::ReadWrite => ProtFlags::PROT_READ | ProtFlags::PROT_WRITE,
+        }
+    }
+}
+
+/// Returns the address of the page containing `address`.
+pub(crate) fn page_of(address: u64) -> u64 {
+    address & !(*PAGE_SIZE - 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rounds_addresses_down_to_the_systems_pages() {
+        let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) } as u64;
+        let page = 0x7f00_0000_0000;
+
+        assert_eq!(page, page_of(page + page_size - 1));
+        assert_eq!(page + page_size, page_of(page + page_size));
+    }
+}
@@ -0,0 +1,210 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use crate::deferred_reprotect;
+use crate::error;
+use crate::module_info;
+use crate::protection::{self, Protection};
+use crate::{GotHook, SearchStrategy};
+
+/// A page holding GOT entries we hooked.
+struct HookedPage {
+    /// The number of our hooks on the page.
+    hooks: usize,
+
+    /// The page's protection before we first hooked it.
+    original_protection: Protection,
+
+    /// The page's current protection.
+    protection: Protection,
+}
+
+lazy_static::lazy_static! {
+    // The GOT entries we hooked, with the number of our hooks on each of them.
+    static ref HOOKED_GOT_ENTRIES: Mutex<HashMap<u64, usize>> = Mutex::new(HashMap::new());
+
+    // The pages of the GOT entries we hooked, by address.
+    static ref HOOKED_PAGES: Mutex<HashMap<u64, HookedPage>> = Mutex::new(HashMap::new());
+}
+
+pub(crate) fn register(got_entry: u64) {
+    *HOOKED_GOT_ENTRIES
+        .lock()
+        .unwrap()
+        .entry(got_entry)
+        .or_insert(0) += 1;
+}
+
+pub(crate) fn unregister(got_entry: u64) {
+    let mut hooked_got_entries = HOOKED_GOT_ENTRIES.lock().unwrap();
+    if let Some(count) = hooked_got_entries.get_mut(&got_entry) {
+        *count -= 1;
+        if 0 == *count {
+            hooked_got_entries.remove(&got_entry);
+        }
+    }
+}
+
+pub(crate) fn is_registered(got_entry: u64) -> bool {
+    HOOKED_GOT_ENTRIES.lock().unwrap().contains_key(&got_entry)
+}
+
+/// Records another hook on the page containing `got_entry`.
+///
+/// The page's original protection is recorded by its first hook, so that
+/// hooks of other GOT entries on the page (which find it writable) don't
+/// mistake our protection for it.
+pub(crate) fn acquire_page(got_entry: u64) -> error::Result<()> {
+    let mut hooked_pages = HOOKED_PAGES.lock().unwrap();
+    if let Some(page) = hooked_pages.get_mut(&protection::page_of(got_entry)) {
+        page.hooks += 1;
+        return Ok(());
+    }
+
+    // A page whose reprotect is still deferred was left writable by us.
+    let current_protection = Protection::of_address(got_entry)?;
+    hooked_pages.insert(
+        protection::page_of(got_entry),
+        HookedPage {
+            hooks: 1,
+            original_protection: deferred_reprotect::cancel(got_entry)
+                .unwrap_or(current_protection),
+            protection: current_protection,
+        },
+    );
+
+    Ok(())
+}
+
+/// Releases a hook on the page containing `got_entry`, restoring the page's
+/// original protection once its last hook is released (unless that's
+/// deferred, see [`defer_reprotects`](crate::defer_reprotects)).
+pub(crate) fn release_page(got_entry: u64) -> error::Result<()> {
+    let mut hooked_pages = HOOKED_PAGES.lock().unwrap();
+    let address = protection::page_of(got_entry);
+    let Some(page) = hooked_pages.get_mut(&address) else {
+        return Ok(());
+    };
+
+    page.hooks -= 1;
+    if 0 != page.hooks {
+        return Ok(());
+    }
+
+    let page = hooked_pages.remove(&address).unwrap();
+    if page.protection == page.original_protection {
+        return Ok(());
+    }
+
+    deferred_reprotect::reprotect(got_entry, page.original_protection)
+}
+
+/// Applies `page_protection` to the hooked page containing `got_entry`.
+pub(crate) fn protect_page(got_entry: u64, page_protection: Protection) -> error::Result<()> {
+    let mut hooked_pages = HOOKED_PAGES.lock().unwrap();
+    page_protection.apply(got_entry)?;
+
+    if let Some(page) = hooked_pages.get_mut(&protection::page_of(got_entry)) {
+        page.protection = page_protection;
+    }
+
+    Ok(())
+}
+
+/// Returns whether we changed the protection of the hooked page containing
+/// `got_entry`.
+pub(crate) fn is_page_protection_modified(got_entry: u64) -> bool {
+    HOOKED_PAGES
+        .lock()
+        .unwrap()
+        .get(&protection::page_of(got_entry))
+        .map(|p| p.protection != p.original_protection)
+        .unwrap_or(false)
+}
+
+/// Checks whether this library currently hooks `function` in `module`.
+///
+/// `module` is either a loaded module's path or its file name.
+pub fn is_hooked(module: &str, function: &str) -> error::Result<bool> {
+    let module = module_info::find_loaded_module(module)?;
+
+    // A GOT scan can't find a hooked GOT entry, it no longer holds the function.
+    for strategy in [
+        SearchStrategy::PltRelocation,
+        SearchStrategy::GlobDatRelocation,
+    ] {
+        match GotHook::resolve_got_entry(module.base_address, function, strategy) {
+            Ok(target) if is_registered(target.got_entry) => return Ok(true),
+            Ok(_) | Err(error::Error::NoGotEntryForFunction(_)) => continue,
+            Err(e) => return Err(e),
+        }
+    }
+
+    Ok(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use object::elf;
+
+    use super::*;
+    use crate::test_elf::{self, ElfBuilder};
+
+    #[test]
+    fn tracks_installed_hooks() {
+        let _lock = test_elf::lock_loaded_modules();
+        let image = ElfBuilder::new(elf::EM_AARCH64)
+            .import("read")
+            .import("write")
+            .build()
+            .map_file("libgothook-registry.so");
+        let module = image.path().to_str().unwrap();
+
+        assert!(!is_hooked(module, "read").unwrap());
+
+        let hook =
+            GotHook::new_with_base(image.base_address(), "read", 0x1000 as *const ()).unwrap();
+        assert!(is_hooked(module, "read").unwrap());
+        // Modules can also be named by their file name.
+        let file_name = image.path().file_name().unwrap().to_str().unwrap();
+        assert!(is_hooked(file_name, "read").unwrap());
+        assert!(!is_hooked(module, "write").unwrap());
+
+        hook.unhook().unwrap();
+        assert!(!is_hooked(module, "read").unwrap());
+    }
+
+    #[test]
+    fn restores_the_page_protection_with_its_last_hook() {
+        let _lock = test_elf::lock_reprotects();
+
+        let image = ElfBuilder::new(elf::EM_AARCH64)
+            .import("read")
+            .import("write")
+            .build()
+            .map();
+        // As after RELRO.
+        Protection::ReadOnly.apply(image.got_entry("read")).unwrap();
+
+        let read_hook =
+            GotHook::new_with_base(image.base_address(), "read", 0x1000 as *const ()).unwrap();
+        // Finds the page writable, as the first hook left it.
+        let write_hook =
+            GotHook::new_with_base(image.base_address(), "write", 0x2000 as *const ()).unwrap();
+        assert!(write_hook.state().page_protection_modified);
+
+        // The other hook on the page still needs it writable.
+        read_hook.unhook().unwrap();
+        assert_eq!(
+            Protection::ReadWrite,
+            Protection::of_address(image.got_entry("write")).unwrap()
+        );
+        assert!(write_hook.state().page_protection_modified);
+
+        write_hook.unhook().unwrap();
+        assert_eq!(
+            Protection::ReadOnly,
+            Protection::of_address(image.got_entry("write")).unwrap()
+        );
+    }
+}
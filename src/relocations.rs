@@ -0,0 +1,367 @@
+pub(crate) use crate::object_relocations::Relocations;
+
+use crate::machine::Machine;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum RelocationSource {
+    Plt,
+    Rela,
+    Rel,
+    Relr,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Relocation {
+    pub(crate) source: RelocationSource,
+    pub(crate) got_address: u64,
+    pub(crate) index: usize,
+    pub(crate) offset: u64,
+
+    /// Resolved with [`Relocations::symbol_name`], once the relocation's type
+    /// is known to refer to a symbol.
+    pub(crate) symbol_index: u32,
+    pub(crate) reloc_type: u32,
+
+    /// `None` for REL and RELR relocations, whose addend is the relocated word.
+    pub(crate) addend: Option<i64>,
+}
+
+impl Relocation {
+    /// A relative relocation of the word at `offset`, decoded from the
+    /// `index`th entry of a `DT_RELR` table.
+    pub(crate) fn relative(base_address: u64, machine: Machine, index: usize, offset: u64) -> Self {
+        Self {
+            source: RelocationSource::Relr,
+            got_address: base_address + offset,
+            index,
+            offset,
+            symbol_index: 0,
+            reloc_type: machine.relative_type(),
+            addend: None,
+        }
+    }
+}
+
+/// Decodes a `DT_RELR` table into the offsets of the words it relocates,
+/// along with the index of the entry each was decoded from.
+pub(crate) struct RelrEntries<'a> {
+    table: &'a [u8],
+    word_size: u64,
+    big_endian: bool,
+    entry_index: usize,
+    pending_bitmap: Option<(u64, u64)>,
+    next_address: u64,
+}
+
+impl<'a> RelrEntries<'a> {
+    pub(crate) fn new(table: &'a [u8], is_64: bool, big_endian: bool) -> Self {
+        Self {
+            table,
+            word_size: if is_64 { 8 } else { 4 },
+            big_endian,
+            entry_index: 0,
+            pending_bitmap: None,
+            next_address: 0,
+        }
+    }
+
+    fn read_entry(&self) -> Option<u64> {
+        let offset = self.entry_index * self.word_size as usize;
+        let bytes = self.table.get(offset..offset + self.word_size as usize)?;
+
+        Some(match (self.word_size, self.big_endian) {
+            (8, false) => u64::from_le_bytes(bytes.try_into().ok()?),
+            (8, true) => u64::from_be_bytes(bytes.try_into().ok()?),
+            (_, false) => u32::from_le_bytes(bytes.try_into().ok()?) as u64,
+            (_, true) => u32::from_be_bytes(bytes.try_into().ok()?) as u64,
+        })
+    }
+}
+
+impl<'a> Iterator for RelrEntries<'a> {
+    type Item = (usize, u64);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            // Drain the bits of the current bitmap entry first.
+            if let Some((bitmap, address)) = self.pending_bitmap {
+                if 0 != bitmap {
+                    let bit = bitmap.trailing_zeros() as u64;
+                    self.pending_bitmap = Some((bitmap & (bitmap - 1), address));
+
+                    // The bitmap entry was already consumed.
+                    return Some((self.entry_index - 1, address + bit * self.word_size));
+                }
+
+                self.pending_bitmap = None;
+            }
+
+            // Read the next RELR entry.
+            let entry = self.read_entry()?;
+            self.entry_index += 1;
+
+            if 0 == entry & 1 {
+                // An even entry is an address to relocate.
+                self.next_address = entry + self.word_size;
+                return Some((self.entry_index - 1, entry));
+            }
+
+            // An odd entry is a bitmap of the words following the last address.
+            // Bit 0 is the marker, so bit N (N >= 1) relocates word N - 1.
+            let bitmap = entry >> 1;
+            let address = self.next_address;
+            self.next_address += (self.word_size * 8 - 1) * self.word_size;
+            self.pending_bitmap = Some((bitmap, address));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use object::elf::{self, FileHeader32, FileHeader64};
+    use object::endian::Endianness;
+    use object::read::elf::FileHeader;
+
+    use super::*;
+    use crate::test_elf::{ElfBuilder, HashTable, TestElf};
+
+    const BASE_ADDRESS: u64 = 0x7f00_0000_0000;
+
+    type Entry = (RelocationSource, u64, Option<String>, u32, Option<i64>);
+
+    fn collect<Elf: FileHeader<Endian = Endianness>>(
+        image: &TestElf,
+        endian: Endianness,
+    ) -> Vec<Entry> {
+        let relocations = Relocations::<Elf>::new(
+            BASE_ADDRESS,
+            Machine::Aarch64,
+            &image.bytes,
+            image.dynamic_segment::<Elf>(),
+            endian,
+        )
+        .unwrap();
+
+        entries(relocations, |relocations, r| {
+            relocations.symbol_name(r).unwrap().map(String::from)
+        })
+        .into_iter()
+        .map(|(_, entry)| entry)
+        .collect()
+    }
+
+    fn entries<R: Iterator<Item = Relocation>>(
+        mut relocations: R,
+        symbol_name: impl Fn(&R, &Relocation) -> Option<String>,
+    ) -> Vec<(usize, Entry)> {
+        let mut entries = Vec::new();
+        while let Some(r) = relocations.next() {
+            assert_eq!(BASE_ADDRESS + r.offset, r.got_address);
+            entries.push((
+                r.index,
+                (
+                    r.source,
+                    r.offset,
+                    symbol_name(&relocations, &r),
+                    r.reloc_type,
+                    r.addend,
+                ),
+            ));
+        }
+        entries
+    }
+
+    #[test]
+    fn iterates_rela_tables() {
+        for (builder, endian) in [
+            (ElfBuilder::new(elf::EM_AARCH64), Endianness::Little),
+            (
+                ElfBuilder::new(elf::EM_AARCH64).big_endian(),
+                Endianness::Big,
+            ),
+        ] {
+            let image = builder
+                .import("read")
+                .import("write")
+                .import_data("environ")
+                .build();
+
+            assert_eq!(
+                collect::<FileHeader64<Endianness>>(&image, endian),
+                [
+                    (
+                        RelocationSource::Plt,
+                        image.got_entry("read"),
+                        Some(String::from("read")),
+                        elf::R_AARCH64_JUMP_SLOT,
+                        Some(0),
+                    ),
+                    (
+                        RelocationSource::Plt,
+                        image.got_entry("write"),
+                        Some(String::from("write")),
+                        elf::R_AARCH64_JUMP_SLOT,
+                        Some(0),
+                    ),
+                    (
+                        RelocationSource::Rela,
+                        image.got_entry("environ"),
+                        Some(String::from("environ")),
+                        elf::R_AARCH64_GLOB_DAT,
+                        Some(0),
+                    ),
+                ]
+            );
+        }
+    }
+
+    #[test]
+    fn iterates_rel_tables_of_32_bit_elfs() {
+        let image = ElfBuilder::new(elf::EM_ARM)
+            .elf32()
+            .import("read")
+            .import_data("environ")
+            .build();
+
+        assert_eq!(
+            collect::<FileHeader32<Endianness>>(&image, Endianness::Little),
+            [
+                (
+                    RelocationSource::Plt,
+                    image.got_entry("read"),
+                    Some(String::from("read")),
+                    elf::R_ARM_JUMP_SLOT,
+                    None,
+                ),
+                (
+                    RelocationSource::Rel,
+                    image.got_entry("environ"),
+                    Some(String::from("environ")),
+                    elf::R_ARM_GLOB_DAT,
+                    None,
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn decodes_relr_bitmaps() {
+        // An address, then bitmaps of the 63 words following it.
+        let image = ElfBuilder::new(elf::EM_AARCH64)
+            .relr(&[0x2000, 0b1011, 0b101])
+            .build();
+
+        let offsets: Vec<u64> = collect::<FileHeader64<Endianness>>(&image, Endianness::Little)
+            .into_iter()
+            .map(|(source, offset, symbol_name, reloc_type, addend)| {
+                assert_eq!(RelocationSource::Relr, source);
+                assert_eq!(None, symbol_name);
+                assert_eq!(elf::R_AARCH64_RELATIVE, reloc_type);
+                assert_eq!(None, addend);
+                offset
+            })
+            .collect();
+
+        assert_eq!(offsets, [0x2000, 0x2008, 0x2018, 0x2008 + 63 * 8 + 8]);
+    }
+
+    #[test]
+    fn decodes_32_bit_relr_bitmaps() {
+        // 32-bit bitmaps cover the 31 words following the address.
+        let image = ElfBuilder::new(elf::EM_ARM)
+            .elf32()
+            .relr(&[0x1000, 1 | 1 << 1 | 1 << 31])
+            .build();
+
+        let offsets: Vec<u64> = collect::<FileHeader32<Endianness>>(&image, Endianness::Little)
+            .into_iter()
+            .map(|(_, offset, ..)| offset)
+            .collect();
+
+        assert_eq!(offsets, [0x1000, 0x1004, 0x1004 + 30 * 4]);
+    }
+
+    #[test]
+    fn ends_derived_plt_tables_at_their_last_jump_slot() {
+        // Without size tags, the PLT relocation table runs into the code that
+        // follows it, which doesn't decode to jump slots.
+        let nop = 0xd503201fu32.to_le_bytes();
+        let image = ElfBuilder::new(elf::EM_AARCH64)
+            .minimal()
+            .import("read")
+            .import("write")
+            .import_data("environ")
+            .code(&nop.repeat(12))
+            .build();
+
+        let entries: Vec<(RelocationSource, u64)> =
+            collect::<FileHeader64<Endianness>>(&image, Endianness::Little)
+                .into_iter()
+                .map(|(source, offset, ..)| (source, offset))
+                .collect();
+
+        assert_eq!(
+            entries,
+            [
+                (RelocationSource::Plt, image.got_entry("read")),
+                (RelocationSource::Plt, image.got_entry("write")),
+                (RelocationSource::Rela, image.got_entry("environ")),
+            ]
+        );
+    }
+
+    #[test]
+    fn names_the_relocations_of_minimal_dynamic_segments() {
+        // Without size tags, the string table's size is derived too.
+        let nop = 0xd503201fu32.to_le_bytes();
+        let image = ElfBuilder::new(elf::EM_AARCH64)
+            .minimal()
+            .import("read")
+            .import_data("environ")
+            .code(&nop.repeat(12))
+            .build();
+
+        let entries: Vec<(RelocationSource, u64, Option<String>)> =
+            collect::<FileHeader64<Endianness>>(&image, Endianness::Little)
+                .into_iter()
+                .map(|(source, offset, symbol_name, ..)| (source, offset, symbol_name))
+                .collect();
+
+        assert_eq!(
+            entries,
+            [
+                (
+                    RelocationSource::Plt,
+                    image.got_entry("read"),
+                    Some(String::from("read"))
+                ),
+                (
+                    RelocationSource::Rela,
+                    image.got_entry("environ"),
+                    Some(String::from("environ"))
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn names_symbols_counted_by_either_hash_table() {
+        for hash_table in [HashTable::Sysv, HashTable::Gnu] {
+            let image = ElfBuilder::new(elf::EM_AARCH64)
+                .hash_table(hash_table)
+                .import("read")
+                .import_data("environ")
+                .build();
+
+            let names: Vec<Option<String>> =
+                collect::<FileHeader64<Endianness>>(&image, Endianness::Little)
+                    .into_iter()
+                    .map(|(_, _, symbol_name, ..)| symbol_name)
+                    .collect();
+            assert_eq!(
+                names,
+                [Some(String::from("read")), Some(String::from("environ"))]
+            );
+        }
+    }
+}
@@ -0,0 +1,20 @@
+pub(crate) struct ResolvedTarget {
+    pub(crate) module_path: Option<String>,
+    pub(crate) got_entry: u64,
+    pub(crate) relocation: Option<MatchedRelocation>,
+}
+
+/// The relocation a GOT entry was located through.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct MatchedRelocation {
+    pub(crate) reloc_type: u32,
+
+    /// The relocation's index within its table.
+    pub(crate) index: usize,
+
+    /// The relocation's raw `r_offset`, relative to the module's base.
+    pub(crate) offset: u64,
+
+    /// The relocation's explicit addend, for RELA relocations.
+    pub(crate) addend: Option<i64>,
+}
@@ -0,0 +1,736 @@
+use std::env;
+use std::ffi::{c_char, c_int, c_uint, c_void, CStr, CString, OsStr};
+use std::fs;
+use std::mem;
+use std::os::unix::ffi::{OsStrExt, OsStringExt};
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::RwLock;
+
+use libc::mode_t;
+use nix::errno::Errno;
+use nix::fcntl::OFlag;
+
+use crate::errno;
+use crate::error;
+use crate::libc_functions::{
+    get_original_function, AccessFunction, FstatatFunction, MkdiratFunction, OpenFunction,
+    OpenatFunction, PathFunction, PathModeFunction, RenameFunction, Renameat2Function,
+    RenameatFunction, StatFunction, StatxFunction, UnlinkatFunction,
+};
+use crate::telemetry;
+use crate::GotHook;
+
+/// What is done with a path a rule matches.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FsAction {
+    Allow,
+
+    /// Fail the call with `EACCES`.
+    Deny,
+
+    /// Replace the rule's path prefix with this path.
+    Redirect(PathBuf),
+}
+
+/// The kind of access a rule applies to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FsAccess {
+    /// Opening a file read-only, the `stat` family and `access`.
+    Read,
+
+    /// Opening a file for writing (or with `O_CREAT`/`O_TRUNC`), `creat`,
+    /// `mkdir`, `rmdir`, `unlink` and both paths of `rename`, and their
+    /// `*at` variants.
+    Write,
+
+    Any,
+}
+
+#[derive(Clone, Debug)]
+struct FsRule {
+    path: PathBuf,
+    access: FsAccess,
+    action: FsAction,
+}
+
+/// An ordered list of rules deciding what happens to each file-system call.
+///
+/// The rules are evaluated in the order they were added, and the first rule
+/// whose path is a prefix of the call's path (component-wise) and whose
+/// access matches the call's access decides. When no rule matches, the
+/// default action decides.
+///
+/// Rule paths are compared with absolute paths without `.` and `..`
+/// components, so they should be given in that form.
+#[derive(Clone, Debug)]
+pub struct FsPolicy {
+    rules: Vec<FsRule>,
+    default_action: FsAction,
+}
+
+impl FsPolicy {
+    pub fn new(default_action: FsAction) -> Self {
+        Self {
+            rules: Vec::new(),
+            default_action,
+        }
+    }
+
+    pub fn rule(mut self, path: impl Into<PathBuf>, access: FsAccess, action: FsAction) -> Self {
+        self.rules.push(FsRule {
+            path: path.into(),
+            access,
+            action,
+        });
+        self
+    }
+
+    /// Returns `None` when the access is denied, and the path to use instead
+    /// of `path` otherwise. `path` must already be resolved (see
+    /// `resolve_path`).
+    fn evaluate(&self, path: &Path, access: FsAccess) -> Option<PathBuf> {
+        let rule = self.rules.iter().find(|r| {
+            path.starts_with(&r.path) && (FsAccess::Any == r.access || access == r.access)
+        });
+
+        match rule.map(|r| &r.action).unwrap_or(&self.default_action) {
+            FsAction::Allow => Some(path.to_path_buf()),
+            FsAction::Deny => None,
+            FsAction::Redirect(target) => {
+                let remainder = rule
+                    .and_then(|r| path.strip_prefix(&r.path).ok())
+                    .filter(|r| !r.as_os_str().is_empty());
+
+                Some(match remainder {
+                    Some(remainder) => target.join(remainder),
+                    None => target.clone(),
+                })
+            }
+        }
+    }
+}
+
+// Indices of the sandboxed functions, in `FUNCTION_NAMES` and
+// `ORIGINAL_FUNCTIONS`.
+const OPEN: usize = 0;
+const OPEN64: usize = 1;
+const OPENAT: usize = 2;
+const OPENAT64: usize = 3;
+const CREAT: usize = 4;
+const STAT: usize = 5;
+const STAT64: usize = 6;
+const LSTAT: usize = 7;
+const LSTAT64: usize = 8;
+const FSTATAT: usize = 9;
+const FSTATAT64: usize = 10;
+const STATX: usize = 11;
+const ACCESS: usize = 12;
+const MKDIR: usize = 13;
+const MKDIRAT: usize = 14;
+const RMDIR: usize = 15;
+const UNLINK: usize = 16;
+const UNLINKAT: usize = 17;
+const RENAME: usize = 18;
+const RENAMEAT: usize = 19;
+const RENAMEAT2: usize = 20;
+const FUNCTION_COUNT: usize = 21;
+
+const FUNCTION_NAMES: [&str; FUNCTION_COUNT] = [
+    "open",
+    "open64",
+    "openat",
+    "openat64",
+    "creat",
+    "stat",
+    "stat64",
+    "lstat",
+    "lstat64",
+    "fstatat",
+    "fstatat64",
+    "statx",
+    "access",
+    "mkdir",
+    "mkdirat",
+    "rmdir",
+    "unlink",
+    "unlinkat",
+    "rename",
+    "renameat",
+    "renameat2",
+];
+
+static ORIGINAL_FUNCTIONS: [AtomicU64; FUNCTION_COUNT] =
+    [const { AtomicU64::new(0) }; FUNCTION_COUNT];
+
+static SANDBOX_ACTIVE: AtomicBool = AtomicBool::new(false);
+
+lazy_static::lazy_static! {
+    // Set once every hook is installed: until then, calls are forwarded as is.
+    static ref POLICY: RwLock<Option<FsPolicy>> = RwLock::new(None);
+}
+
+/// The sandbox is removed when this is dropped.
+pub struct FsSandbox {
+    hooks: Vec<GotHook>,
+}
+
+impl Drop for FsSandbox {
+    fn drop(&mut self) {
+        *POLICY.write().unwrap() = None;
+
+        while let Some(hook) = self.hooks.pop() {
+            drop(hook);
+        }
+
+        SANDBOX_ACTIVE.store(false, Ordering::Release);
+    }
+}
+
+/// Hooks the main executable's file-system functions, routing each call
+/// through `policy`.
+///
+/// The covered functions are `open`, `open64`, `openat`, `openat64`, `creat`,
+/// `stat`, `stat64`, `lstat`, `lstat64`, `fstatat`, `fstatat64`, `statx`,
+/// `access`, `mkdir`, `mkdirat`, `rmdir`, `unlink`, `unlinkat`, `rename`,
+/// `renameat` and `renameat2`. Functions the main executable doesn't import
+/// are skipped.
+///
+/// Only the main executable's calls are sandboxed: calls the libraries make
+/// (including libc's own calls, e.g. `fopen` calling `open`) don't go through
+/// its GOT, and neither do direct system calls.
+///
+/// Before the policy is evaluated, paths are resolved to absolute paths:
+/// relative paths against the current directory (or the directory of an
+/// `*at` function's descriptor), and `.` and `..` components lexically. The
+/// resolved path is the one forwarded to the function. Symbolic links aren't
+/// resolved, so a rule doesn't cover what a link inside its path points to.
+/// Calls whose path can't be resolved are denied.
+///
+/// Only one sandbox can be active at a time.
+pub fn sandbox_filesystem(policy: FsPolicy) -> error::Result<FsSandbox> {
+    if SANDBOX_ACTIVE
+        .compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire)
+        .is_err()
+    {
+        return Err(error::Error::SandboxAlreadyActive);
+    }
+
+    // Dropping the sandbox on failure removes the hooks installed so far.
+    let mut sandbox = FsSandbox { hooks: Vec::new() };
+
+    let callbacks: [*const (); FUNCTION_COUNT] = [
+        open_callback::<OPEN> as *const (),
+        open_callback::<OPEN64> as *const (),
+        openat_callback::<OPENAT> as *const (),
+        openat_callback::<OPENAT64> as *const (),
+        creat_callback as *const (),
+        stat_callback::<STAT> as *const (),
+        stat_callback::<STAT64> as *const (),
+        stat_callback::<LSTAT> as *const (),
+        stat_callback::<LSTAT64> as *const (),
+        fstatat_callback::<FSTATAT> as *const (),
+        fstatat_callback::<FSTATAT64> as *const (),
+        statx_callback as *const (),
+        access_callback as *const (),
+        mkdir_callback as *const (),
+        mkdirat_callback as *const (),
+        path_callback::<RMDIR> as *const (),
+        path_callback::<UNLINK> as *const (),
+        unlinkat_callback as *const (),
+        rename_callback as *const (),
+        renameat_callback as *const (),
+        renameat2_callback as *const (),
+    ];
+
+    for (function, callback) in callbacks.into_iter().enumerate() {
+        let mut hook = match GotHook::new_in_main_executable(FUNCTION_NAMES[function], callback) {
+            Ok(hook) => hook,
+            Err(error::Error::NoGotEntryForFunction(_)) => continue,
+            Err(e) => return Err(e),
+        };
+
+        hook.mirror_original_function(&ORIGINAL_FUNCTIONS[function]);
+        sandbox.hooks.push(hook);
+    }
+
+    // Hooking opens files, so the policy is only enforced from now on.
+    *POLICY.write().unwrap() = Some(policy);
+
+    Ok(sandbox)
+}
+
+/// Returns the original function.
+fn original_function(function: usize) -> Option<*const ()> {
+    Some(get_original_function(
+        FUNCTION_NAMES[function],
+        &ORIGINAL_FUNCTIONS[function],
+    ))
+    .filter(|f| !f.is_null())
+}
+
+/// Returns the function to forward the call to and the path to forward it
+/// with, or `None` if the call is denied.
+///
+/// Functions with a second path sandbox it with `sandbox_path`.
+fn apply_policy(
+    function: usize,
+    dirfd: c_int,
+    path: *const c_char,
+    access: FsAccess,
+) -> Option<(*const (), SandboxedPath)> {
+    telemetry::call_intercepted();
+
+    let original_function = original_function(function)?;
+    let sandboxed_path = sandbox_path(dirfd, path, access)?;
+
+    Some((original_function, sandboxed_path))
+}
+
+/// The path a call is forwarded with.
+struct SandboxedPath {
+    path: *const c_char,
+    replacement: Option<CString>,
+}
+
+impl SandboxedPath {
+    fn as_ptr(&self) -> *const c_char {
+        self.replacement.as_ref().map_or(self.path, |p| p.as_ptr())
+    }
+}
+
+/// Returns the path to forward to the function instead of `path`,
+/// relative to `dirfd`, or `None` if the access is denied.
+fn sandbox_path(dirfd: c_int, path: *const c_char, access: FsAccess) -> Option<SandboxedPath> {
+    let forward = SandboxedPath {
+        path,
+        replacement: None,
+    };
+
+    let policy = POLICY.read().unwrap();
+
+    // Let the original function deal with invalid paths.
+    let Some(policy) = policy.as_ref().filter(|_| !path.is_null()) else {
+        return Some(forward);
+    };
+
+    let original_path = Path::new(OsStr::from_bytes(
+        unsafe { CStr::from_ptr(path) }.to_bytes(),
+    ));
+    let resolved_path = resolve_path(dirfd, original_path)?;
+    let sandboxed_path = policy.evaluate(&resolved_path, access)?;
+    if sandboxed_path == original_path {
+        return Some(forward);
+    }
+
+    Some(SandboxedPath {
+        path,
+        replacement: Some(CString::new(sandboxed_path.into_os_string().into_vec()).ok()?),
+    })
+}
+
+/// Resolves `path`, relative to `dirfd`, to an absolute path without `.` and
+/// `..` components. Symbolic links aren't resolved.
+fn resolve_path(dirfd: c_int, path: &Path) -> Option<PathBuf> {
+    let absolute_path = if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        let directory = if libc::AT_FDCWD == dirfd {
+            env::current_dir().ok()?
+        } else {
+            fs::read_link(format!("/proc/self/fd/{dirfd}")).ok()?
+        };
+
+        // e.g. the "(deleted)" paths of removed directories.
+        if !directory.is_absolute() {
+            return None;
+        }
+
+        directory.join(path)
+    };
+
+    let mut resolved_path = PathBuf::new();
+    for component in absolute_path.components() {
+        match component {
+            Component::RootDir => resolved_path.push(Component::RootDir),
+            Component::CurDir => {}
+            // The root's parent is the root itself.
+            Component::ParentDir => {
+                resolved_path.pop();
+            }
+            Component::Normal(name) => resolved_path.push(name),
+            Component::Prefix(_) => return None,
+        }
+    }
+
+    Some(resolved_path)
+}
+
+fn deny() -> c_int {
+    errno::set_errno(Errno::EACCES);
+    -1
+}
+
+fn open_access(flags: c_int) -> FsAccess {
+    let flags = OFlag::from_bits_truncate(flags);
+    if OFlag::O_RDONLY == flags & OFlag::O_ACCMODE
+        && !flags.intersects(OFlag::O_CREAT | OFlag::O_TRUNC)
+    {
+        FsAccess::Read
+    } else {
+        FsAccess::Write
+    }
+}
+
+extern "C" fn open_callback<const FUNCTION: usize>(
+    pathname: *const c_char,
+    flags: c_int,
+    mode: mode_t,
+) -> c_int {
+    let Some((original_function, path)) =
+        apply_policy(FUNCTION, libc::AT_FDCWD, pathname, open_access(flags))
+    else {
+        return deny();
+    };
+
+    let original_open = unsafe { mem::transmute::<*const (), OpenFunction>(original_function) };
+    unsafe { original_open(path.as_ptr(), flags, mode) }
+}
+
+extern "C" fn openat_callback<const FUNCTION: usize>(
+    dirfd: c_int,
+    pathname: *const c_char,
+    flags: c_int,
+    mode: mode_t,
+) -> c_int {
+    let Some((original_function, path)) =
+        apply_policy(FUNCTION, dirfd, pathname, open_access(flags))
+    else {
+        return deny();
+    };
+
+    let original_openat = unsafe { mem::transmute::<*const (), OpenatFunction>(original_function) };
+    unsafe { original_openat(dirfd, path.as_ptr(), flags, mode) }
+}
+
+extern "C" fn creat_callback(pathname: *const c_char, mode: mode_t) -> c_int {
+    let Some((original_function, path)) =
+        apply_policy(CREAT, libc::AT_FDCWD, pathname, FsAccess::Write)
+    else {
+        return deny();
+    };
+
+    let original_creat =
+        unsafe { mem::transmute::<*const (), PathModeFunction>(original_function) };
+    unsafe { original_creat(path.as_ptr(), mode) }
+}
+
+extern "C" fn stat_callback<const FUNCTION: usize>(
+    pathname: *const c_char,
+    statbuf: *mut c_void,
+) -> c_int {
+    let Some((original_function, path)) =
+        apply_policy(FUNCTION, libc::AT_FDCWD, pathname, FsAccess::Read)
+    else {
+        return deny();
+    };
+
+    let original_stat = unsafe { mem::transmute::<*const (), StatFunction>(original_function) };
+    unsafe { original_stat(path.as_ptr(), statbuf) }
+}
+
+extern "C" fn fstatat_callback<const FUNCTION: usize>(
+    dirfd: c_int,
+    pathname: *const c_char,
+    statbuf: *mut c_void,
+    flags: c_int,
+) -> c_int {
+    let Some((original_function, path)) = apply_policy(FUNCTION, dirfd, pathname, FsAccess::Read)
+    else {
+        return deny();
+    };
+
+    let original_fstatat =
+        unsafe { mem::transmute::<*const (), FstatatFunction>(original_function) };
+    unsafe { original_fstatat(dirfd, path.as_ptr(), statbuf, flags) }
+}
+
+extern "C" fn statx_callback(
+    dirfd: c_int,
+    pathname: *const c_char,
+    flags: c_int,
+    mask: c_uint,
+    statxbuf: *mut c_void,
+) -> c_int {
+    let Some((original_function, path)) = apply_policy(STATX, dirfd, pathname, FsAccess::Read)
+    else {
+        return deny();
+    };
+
+    let original_statx = unsafe { mem::transmute::<*const (), StatxFunction>(original_function) };
+    unsafe { original_statx(dirfd, path.as_ptr(), flags, mask, statxbuf) }
+}
+
+extern "C" fn access_callback(pathname: *const c_char, mode: c_int) -> c_int {
+    let Some((original_function, path)) =
+        apply_policy(ACCESS, libc::AT_FDCWD, pathname, FsAccess::Read)
+    else {
+        return deny();
+    };
+
+    let original_access = unsafe { mem::transmute::<*const (), AccessFunction>(original_function) };
+    unsafe { original_access(path.as_ptr(), mode) }
+}
+
+extern "C" fn mkdir_callback(pathname: *const c_char, mode: mode_t) -> c_int {
+    let Some((original_function, path)) =
+        apply_policy(MKDIR, libc::AT_FDCWD, pathname, FsAccess::Write)
+    else {
+        return deny();
+    };
+
+    let original_mkdir =
+        unsafe { mem::transmute::<*const (), PathModeFunction>(original_function) };
+    unsafe { original_mkdir(path.as_ptr(), mode) }
+}
+
+extern "C" fn mkdirat_callback(dirfd: c_int, pathname: *const c_char, mode: mode_t) -> c_int {
+    let Some((original_function, path)) = apply_policy(MKDIRAT, dirfd, pathname, FsAccess::Write)
+    else {
+        return deny();
+    };
+
+    let original_mkdirat =
+        unsafe { mem::transmute::<*const (), MkdiratFunction>(original_function) };
+    unsafe { original_mkdirat(dirfd, path.as_ptr(), mode) }
+}
+
+extern "C" fn path_callback<const FUNCTION: usize>(pathname: *const c_char) -> c_int {
+    let Some((original_function, path)) =
+        apply_policy(FUNCTION, libc::AT_FDCWD, pathname, FsAccess::Write)
+    else {
+        return deny();
+    };
+
+    let original_function = unsafe { mem::transmute::<*const (), PathFunction>(original_function) };
+    unsafe { original_function(path.as_ptr()) }
+}
+
+extern "C" fn unlinkat_callback(dirfd: c_int, pathname: *const c_char, flags: c_int) -> c_int {
+    let Some((original_function, path)) = apply_policy(UNLINKAT, dirfd, pathname, FsAccess::Write)
+    else {
+        return deny();
+    };
+
+    let original_unlinkat =
+        unsafe { mem::transmute::<*const (), UnlinkatFunction>(original_function) };
+    unsafe { original_unlinkat(dirfd, path.as_ptr(), flags) }
+}
+
+extern "C" fn rename_callback(oldpath: *const c_char, newpath: *const c_char) -> c_int {
+    let Some((original_function, old_path)) =
+        apply_policy(RENAME, libc::AT_FDCWD, oldpath, FsAccess::Write)
+    else {
+        return deny();
+    };
+    let Some(new_path) = sandbox_path(libc::AT_FDCWD, newpath, FsAccess::Write) else {
+        return deny();
+    };
+
+    let original_rename = unsafe { mem::transmute::<*const (), RenameFunction>(original_function) };
+    unsafe { original_rename(old_path.as_ptr(), new_path.as_ptr()) }
+}
+
+extern "C" fn renameat_callback(
+    olddirfd: c_int,
+    oldpath: *const c_char,
+    newdirfd: c_int,
+    newpath: *const c_char,
+) -> c_int {
+    let Some((original_function, old_path)) =
+        apply_policy(RENAMEAT, olddirfd, oldpath, FsAccess::Write)
+    else {
+        return deny();
+    };
+    let Some(new_path) = sandbox_path(newdirfd, newpath, FsAccess::Write) else {
+        return deny();
+    };
+
+    let original_renameat =
+        unsafe { mem::transmute::<*const (), RenameatFunction>(original_function) };
+    unsafe { original_renameat(olddirfd, old_path.as_ptr(), newdirfd, new_path.as_ptr()) }
+}
+
+extern "C" fn renameat2_callback(
+    olddirfd: c_int,
+    oldpath: *const c_char,
+    newdirfd: c_int,
+    newpath: *const c_char,
+    flags: c_uint,
+) -> c_int {
+    let Some((original_function, old_path)) =
+        apply_policy(RENAMEAT2, olddirfd, oldpath, FsAccess::Write)
+    else {
+        return deny();
+    };
+    let Some(new_path) = sandbox_path(newdirfd, newpath, FsAccess::Write) else {
+        return deny();
+    };
+
+    let original_renameat2 =
+        unsafe { mem::transmute::<*const (), Renameat2Function>(original_function) };
+    unsafe {
+        original_renameat2(
+            olddirfd,
+            old_path.as_ptr(),
+            newdirfd,
+            new_path.as_ptr(),
+            flags,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+    use std::sync::Mutex;
+
+    use super::*;
+
+    // The policy is global.
+    static POLICY_LOCK: Mutex<()> = Mutex::new(());
+
+    fn c_path(path: impl AsRef<Path>) -> CString {
+        CString::new(path.as_ref().as_os_str().as_bytes()).unwrap()
+    }
+
+    fn open_directory(path: &Path) -> c_int {
+        let fd = unsafe { libc::open(c_path(path).as_ptr(), libc::O_RDONLY | libc::O_DIRECTORY) };
+        assert!(0 <= fd);
+        fd
+    }
+
+    fn assert_denied(result: c_int) {
+        assert_eq!(-1, result);
+        assert_eq!(
+            Some(libc::EACCES),
+            io::Error::last_os_error().raw_os_error()
+        );
+    }
+
+    #[test]
+    fn resolves_paths_lexically() {
+        assert_eq!(
+            Some(PathBuf::from("/a/c/d")),
+            resolve_path(libc::AT_FDCWD, Path::new("/a/b/../c/./d"))
+        );
+        assert_eq!(
+            Some(PathBuf::from("/etc")),
+            resolve_path(libc::AT_FDCWD, Path::new("/../../etc"))
+        );
+        assert_eq!(
+            Some(env::current_dir().unwrap().join("b")),
+            resolve_path(libc::AT_FDCWD, Path::new("a/../b"))
+        );
+    }
+
+    #[test]
+    fn resolves_paths_relative_to_directory_descriptors() {
+        let directory = env::temp_dir().canonicalize().unwrap();
+        let fd = open_directory(&directory);
+
+        assert_eq!(
+            Some(directory.parent().unwrap().join("file")),
+            resolve_path(fd, Path::new("../file"))
+        );
+        // Absolute paths ignore the descriptor.
+        assert_eq!(
+            Some(PathBuf::from("/file")),
+            resolve_path(fd, Path::new("/file"))
+        );
+        unsafe { libc::close(fd) };
+
+        // An invalid descriptor can't be resolved.
+        assert_eq!(None, resolve_path(-2, Path::new("file")));
+    }
+
+    #[test]
+    fn denies_writes_outside_a_directory_and_redirects_a_read() {
+        let _lock = POLICY_LOCK.lock().unwrap();
+
+        let directory = env::temp_dir()
+            .canonicalize()
+            .unwrap()
+            .join(format!("gothook-sandbox-{}", std::process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        fs::write(directory.join("secret"), "secret").unwrap();
+        fs::write(directory.join("decoy"), "decoy").unwrap();
+        let fd = open_directory(&directory);
+
+        *POLICY.write().unwrap() = Some(
+            FsPolicy::new(FsAction::Allow)
+                .rule(&directory, FsAccess::Write, FsAction::Allow)
+                .rule("/", FsAccess::Write, FsAction::Deny)
+                .rule(
+                    directory.join("secret"),
+                    FsAccess::Read,
+                    FsAction::Redirect(directory.join("decoy")),
+                ),
+        );
+
+        // Writing inside the directory is allowed.
+        let write_flags = libc::O_WRONLY | libc::O_CREAT;
+        let inside = open_callback::<OPEN>(
+            c_path(directory.join("inside")).as_ptr(),
+            write_flags,
+            0o600,
+        );
+        assert!(0 <= inside);
+        unsafe { libc::close(inside) };
+        let inside = openat_callback::<OPENAT>(fd, c"inside-at".as_ptr(), write_flags, 0o600);
+        assert!(0 <= inside);
+        unsafe { libc::close(inside) };
+
+        // Escaping it, with `..` or a relative path, isn't.
+        assert_denied(open_callback::<OPEN64>(
+            c_path(directory.join("../gothook-escape")).as_ptr(),
+            write_flags,
+            0o600,
+        ));
+        assert_denied(openat_callback::<OPENAT64>(
+            fd,
+            c"../gothook-escape".as_ptr(),
+            write_flags,
+            0o600,
+        ));
+        assert_denied(mkdirat_callback(fd, c"../gothook-escape".as_ptr(), 0o700));
+        assert_denied(unlinkat_callback(fd, c"../gothook-escape".as_ptr(), 0));
+        assert_denied(renameat2_callback(
+            fd,
+            c"inside".as_ptr(),
+            fd,
+            c"../gothook-escape".as_ptr(),
+            0,
+        ));
+
+        // The secret is redirected to the decoy.
+        let secret = openat_callback::<OPENAT>(fd, c"secret".as_ptr(), libc::O_RDONLY, 0);
+        assert!(0 <= secret);
+        let mut buffer = [0u8; 16];
+        let size = unsafe { libc::read(secret, buffer.as_mut_ptr() as *mut c_void, buffer.len()) };
+        assert_eq!(b"decoy", &buffer[..size as usize]);
+        unsafe { libc::close(secret) };
+
+        // A descriptor that can't be resolved denies the call.
+        unsafe { libc::close(fd) };
+        assert_denied(openat_callback::<OPENAT>(
+            -2,
+            c"secret".as_ptr(),
+            libc::O_RDONLY,
+            0,
+        ));
+
+        *POLICY.write().unwrap() = None;
+        fs::remove_dir_all(&directory).unwrap();
+    }
+}
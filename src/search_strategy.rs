@@ -0,0 +1,58 @@
+use std::fmt;
+
+use crate::error;
+
+/// A way of locating a function's GOT entry.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SearchStrategy {
+    /// A jump slot relocation in the PLT relocation table (`DT_JMPREL`).
+    PltRelocation,
+
+    /// A glob-dat relocation in the dynamic relocation tables (`DT_RELA`/`DT_REL`).
+    GlobDatRelocation,
+
+    /// A jump slot or glob-dat relocation in the relocation sections of the
+    /// module's file, for modules whose in-memory tables are unreadable.
+    OnDiskRelocation,
+
+    /// A GOT slot holding the address `dlsym` resolves the function to. Only
+    /// the GOT is searched: the part of the `PT_GNU_RELRO` segment from the
+    /// first entry a jump slot or glob-dat relocation points at (leaving out
+    /// the `.data.rel.ro`, `.init_array` and `.dynamic` data preceding it),
+    /// and the PLT's GOT at `DT_PLTGOT`, which holds a jump slot per PLT
+    /// relocation.
+    GotScan,
+}
+
+impl SearchStrategy {
+    pub(crate) const ALL: [SearchStrategy; 4] = [
+        SearchStrategy::PltRelocation,
+        SearchStrategy::GlobDatRelocation,
+        SearchStrategy::OnDiskRelocation,
+        SearchStrategy::GotScan,
+    ];
+}
+
+impl fmt::Display for SearchStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            SearchStrategy::PltRelocation => "PLT relocation",
+            SearchStrategy::GlobDatRelocation => "glob-dat relocation",
+            SearchStrategy::OnDiskRelocation => "on-disk relocation",
+            SearchStrategy::GotScan => "GOT scan",
+        };
+
+        f.write_str(name)
+    }
+}
+
+/// Describes how [`GotHook::new_auto`](crate::GotHook::new_auto) located the
+/// function's GOT entry.
+#[derive(Debug)]
+pub struct SearchReport {
+    /// The strategy that located the GOT entry.
+    pub strategy: SearchStrategy,
+
+    /// The strategies attempted before it, and why each of them failed.
+    pub failures: Vec<(SearchStrategy, error::Error)>,
+}
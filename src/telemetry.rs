@@ -0,0 +1,182 @@
+// Metrics exported through the `metrics` facade when the `metrics` feature is
+// enabled. Without it, these compile down to nothing.
+//
+// Intercepted calls are only counted by the callbacks this crate provides
+// (the tracing, sandboxing and errno hooks): other user callbacks are called
+// directly through the GOT, without going through the crate.
+
+#[cfg(feature = "metrics")]
+const INSTALLED_HOOKS: &str = "gothook_installed_hooks";
+#[cfg(feature = "metrics")]
+const INTERCEPTED_CALLS: &str = "gothook_intercepted_calls";
+#[cfg(feature = "metrics")]
+const RESTORE_FAILURES: &str = "gothook_restore_failures";
+
+#[inline]
+pub(crate) fn hook_installed() {
+    #[cfg(feature = "metrics")]
+    ::metrics::gauge!(INSTALLED_HOOKS).increment(1.0);
+}
+
+#[inline]
+pub(crate) fn hook_removed() {
+    #[cfg(feature = "metrics")]
+    ::metrics::gauge!(INSTALLED_HOOKS).decrement(1.0);
+}
+
+#[inline]
+pub(crate) fn call_intercepted() {
+    #[cfg(feature = "metrics")]
+    ::metrics::counter!(INTERCEPTED_CALLS).increment(1);
+}
+
+#[inline]
+pub(crate) fn restore_failed() {
+    #[cfg(feature = "metrics")]
+    ::metrics::counter!(RESTORE_FAILURES).increment(1);
+}
+
+#[cfg(all(test, feature = "metrics"))]
+mod tests {
+    use std::collections::HashMap;
+    use std::env;
+    use std::fs::{self, File};
+    use std::os::fd::AsRawFd;
+    use std::process;
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::{Arc, Mutex};
+    use std::{ptr, slice};
+
+    use metrics::{
+        Counter, Gauge, Histogram, Key, KeyName, Metadata, Recorder, SharedString, Unit,
+    };
+    use object::elf;
+
+    use super::*;
+    use crate::error;
+    use crate::protection;
+    use crate::test_elf::ElfBuilder;
+    use crate::GotHook;
+
+    /// Captures the counters and gauges, by name.
+    #[derive(Default)]
+    struct CapturingRecorder {
+        metrics: Mutex<HashMap<String, Arc<AtomicU64>>>,
+    }
+
+    impl CapturingRecorder {
+        fn metric(&self, name: &str) -> Arc<AtomicU64> {
+            self.metrics
+                .lock()
+                .unwrap()
+                .entry(String::from(name))
+                .or_default()
+                .clone()
+        }
+
+        fn counter_value(&self, name: &str) -> u64 {
+            self.metric(name).load(Ordering::Acquire)
+        }
+
+        fn gauge_value(&self, name: &str) -> f64 {
+            f64::from_bits(self.metric(name).load(Ordering::Acquire))
+        }
+    }
+
+    impl Recorder for CapturingRecorder {
+        fn describe_counter(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+
+        fn describe_gauge(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+
+        fn describe_histogram(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+
+        fn register_counter(&self, key: &Key, _: &Metadata<'_>) -> Counter {
+            Counter::from_arc(self.metric(key.name()))
+        }
+
+        fn register_gauge(&self, key: &Key, _: &Metadata<'_>) -> Gauge {
+            Gauge::from_arc(self.metric(key.name()))
+        }
+
+        fn register_histogram(&self, _: &Key, _: &Metadata<'_>) -> Histogram {
+            Histogram::noop()
+        }
+    }
+
+    #[test]
+    fn exports_hook_metrics() {
+        let recorder = CapturingRecorder::default();
+        let image = ElfBuilder::new(elf::EM_AARCH64)
+            .import("read")
+            .import("write")
+            .build()
+            .map();
+
+        metrics::with_local_recorder(&recorder, || {
+            let read_hook =
+                GotHook::new_with_base(image.base_address(), "read", 0x1000 as *const ()).unwrap();
+            let write_hook =
+                GotHook::new_with_base(image.base_address(), "write", 0x1000 as *const ()).unwrap();
+            assert_eq!(2.0, recorder.gauge_value(INSTALLED_HOOKS));
+
+            read_hook.unhook().unwrap();
+            assert_eq!(1.0, recorder.gauge_value(INSTALLED_HOOKS));
+
+            // Hooked on top of us.
+            image.write_got_entry("write", 0x2000);
+            assert!(write_hook.unhook().is_err());
+            assert_eq!(0.0, recorder.gauge_value(INSTALLED_HOOKS));
+            assert_eq!(1, recorder.counter_value(RESTORE_FAILURES));
+        });
+    }
+
+    #[test]
+    fn counts_pages_that_cant_be_made_writable_as_restore_failures() {
+        let recorder = CapturingRecorder::default();
+        let image = ElfBuilder::new(elf::EM_AARCH64)
+            .import("read")
+            .build()
+            .map();
+
+        metrics::with_local_recorder(&recorder, || {
+            let hook =
+                GotHook::new_with_base(image.base_address(), "read", 0x1000 as *const ()).unwrap();
+
+            // Replace the GOT's page with a shared mapping of a read-only
+            // file, which can't be made writable.
+            let page = protection::page_of(image.got_entry("read"));
+            let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) } as usize;
+            let image_end = (image.base_address() + image.size()) as usize;
+            let mut contents = unsafe {
+                slice::from_raw_parts(page as *const u8, page_size.min(image_end - page as usize))
+            }
+            .to_vec();
+            contents.resize(page_size, 0);
+            let path = env::temp_dir().join(format!("gothook-read-only-{}", process::id()));
+            fs::write(&path, contents).unwrap();
+            let file = File::open(&path).unwrap();
+            let address = unsafe {
+                libc::mmap(
+                    page as *mut libc::c_void,
+                    page_size,
+                    libc::PROT_READ,
+                    libc::MAP_SHARED | libc::MAP_FIXED,
+                    file.as_raw_fd(),
+                    0,
+                )
+            };
+            fs::remove_file(&path).unwrap();
+            assert_eq!(page as *mut libc::c_void, address);
+
+            assert!(matches!(
+                hook.unhook(),
+                Err(error::Error::ModifyMemoryPageProtection(_, p)) if p == page
+            ));
+            assert_eq!(0.0, recorder.gauge_value(INSTALLED_HOOKS));
+            assert_eq!(1, recorder.counter_value(RESTORE_FAILURES));
+            assert_eq!(0x1000, unsafe {
+                ptr::read_volatile(image.got_entry("read") as *const u64)
+            });
+        });
+    }
+}
@@ -0,0 +1,803 @@
+// Synthetic ELF images for tests, laid out the way linkers lay out shared
+// libraries: a read-only page of symbols and relocation tables, followed by a
+// writable page holding the dynamic segment and the GOT.
+
+use std::collections::HashMap;
+use std::env;
+use std::ffi::c_void;
+use std::fs::{self, File};
+use std::os::fd::AsRawFd;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::ptr;
+use std::sync::{Mutex, MutexGuard};
+
+use object::elf;
+use object::read::elf::FileHeader;
+use object::ReadRef;
+
+pub(crate) const PAGE_SIZE: u64 = 4096;
+
+static LOADED_MODULES_LOCK: Mutex<()> = Mutex::new(());
+
+/// Serializes the tests that map images from files, which are then listed as
+/// loaded modules, with the tests that read every loaded module: the images
+/// mustn't be unmapped while they're read.
+pub(crate) fn lock_loaded_modules() -> MutexGuard<'static, ()> {
+    LOADED_MODULES_LOCK
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+}
+
+static REPROTECTS_LOCK: Mutex<()> = Mutex::new(());
+
+/// Serializes the tests that restore read-only pages with the tests deferring
+/// reprotects, which would otherwise defer (and flush) theirs.
+pub(crate) fn lock_reprotects() -> MutexGuard<'static, ()> {
+    REPROTECTS_LOCK.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+struct Symbol {
+    name: String,
+    value: Option<u64>,
+}
+
+/// The hash table the dynamic segment references.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum HashTable {
+    Sysv,
+    Gnu,
+    None,
+}
+
+pub(crate) struct ElfBuilder {
+    is_64: bool,
+    big_endian: bool,
+    machine: u16,
+    flags: u32,
+    is_rela: bool,
+    hash_table: HashTable,
+    symbols: Vec<Symbol>,
+    jump_slots: Vec<usize>,
+    glob_dats: Vec<usize>,
+    relr: Vec<u64>,
+    minimal: bool,
+    sections: bool,
+    dynamic_segments: usize,
+    code: Vec<u8>,
+    link_address: u64,
+}
+
+impl ElfBuilder {
+    /// A 64-bit little-endian ELF with RELA relocations.
+    pub(crate) fn new(machine: u16) -> Self {
+        Self {
+            is_64: true,
+            big_endian: false,
+            machine,
+            flags: 0,
+            is_rela: true,
+            hash_table: HashTable::Sysv,
+            symbols: Vec::new(),
+            jump_slots: Vec::new(),
+            glob_dats: Vec::new(),
+            relr: Vec::new(),
+            minimal: false,
+            sections: false,
+            dynamic_segments: 1,
+            code: Vec::new(),
+            link_address: 0,
+        }
+    }
+
+    /// Makes the ELF 32-bit, with REL relocations.
+    pub(crate) fn elf32(mut self) -> Self {
+        self.is_64 = false;
+        self.is_rela = false;
+        self
+    }
+
+    pub(crate) fn flags(mut self, flags: u32) -> Self {
+        self.flags = flags;
+        self
+    }
+
+    pub(crate) fn big_endian(mut self) -> Self {
+        self.big_endian = true;
+        self
+    }
+
+    pub(crate) fn hash_table(mut self, hash_table: HashTable) -> Self {
+        self.hash_table = hash_table;
+        self
+    }
+
+    /// Imports `name` through a jump slot.
+    pub(crate) fn import(mut self, name: &str) -> Self {
+        let index = self.symbol(name, None);
+        self.jump_slots.push(index);
+        self
+    }
+
+    /// Imports `name` through a glob-dat relocation.
+    pub(crate) fn import_data(mut self, name: &str) -> Self {
+        let index = self.symbol(name, None);
+        self.glob_dats.push(index);
+        self
+    }
+
+    /// Defines `name` at `value`, as a function the module exports.
+    pub(crate) fn define(mut self, name: &str, value: u64) -> Self {
+        self.symbol(name, Some(value));
+        self
+    }
+
+    /// Adds a `DT_RELR` table holding `entries` verbatim.
+    pub(crate) fn relr(mut self, entries: &[u64]) -> Self {
+        self.relr = entries.to_vec();
+        self
+    }
+
+    /// Omits the dynamic segment's size tags, leaving the tables' sizes to be
+    /// derived from the tables that follow them.
+    pub(crate) fn minimal(mut self) -> Self {
+        self.minimal = true;
+        self
+    }
+
+    /// Adds section headers for the dynamic symbols, their strings and the
+    /// relocation tables, past the loaded segments, as they are in files.
+    pub(crate) fn sections(mut self) -> Self {
+        self.sections = true;
+        self
+    }
+
+    /// Lists the dynamic segment in `count` `PT_DYNAMIC` program headers.
+    pub(crate) fn dynamic_segments(mut self, count: usize) -> Self {
+        self.dynamic_segments = count;
+        self
+    }
+
+    /// Places `code` right after the PLT relocation table.
+    pub(crate) fn code(mut self, code: &[u8]) -> Self {
+        self.code = code.to_vec();
+        self
+    }
+
+    /// Links the ELF as an executable loaded at `address`.
+    pub(crate) fn link_address(mut self, address: u64) -> Self {
+        self.link_address = address;
+        self
+    }
+
+    fn symbol(&mut self, name: &str, value: Option<u64>) -> usize {
+        if let Some(index) = self.symbols.iter().position(|s| s.name == name) {
+            return index + 1;
+        }
+
+        self.symbols.push(Symbol {
+            name: String::from(name),
+            value,
+        });
+        self.symbols.len()
+    }
+
+    fn word_size(&self) -> u64 {
+        if self.is_64 {
+            8
+        } else {
+            4
+        }
+    }
+
+    fn relocation_types(&self) -> (u32, u32) {
+        match self.machine {
+            elf::EM_AARCH64 => (elf::R_AARCH64_JUMP_SLOT, elf::R_AARCH64_GLOB_DAT),
+            elf::EM_PPC64 => (elf::R_PPC64_JMP_SLOT, elf::R_PPC64_GLOB_DAT),
+            elf::EM_S390 => (elf::R_390_JMP_SLOT, elf::R_390_GLOB_DAT),
+            elf::EM_ARM => (elf::R_ARM_JUMP_SLOT, elf::R_ARM_GLOB_DAT),
+            _ => (elf::R_X86_64_JUMP_SLOT, elf::R_X86_64_GLOB_DAT),
+        }
+    }
+
+    pub(crate) fn build(self) -> TestElf {
+        let word_size = self.word_size();
+        let (header_size, program_header_size, symbol_size) = if self.is_64 {
+            (64, 56, 24)
+        } else {
+            (52, 32, 16)
+        };
+        let relocation_size = match (self.is_64, self.is_rela) {
+            (true, true) => 24,
+            (true, false) => 16,
+            (false, true) => 12,
+            (false, false) => 8,
+        };
+        let program_headers_count = 3 + self.dynamic_segments as u64;
+
+        // The read-only page: symbols, hash table, relocation tables, strings.
+        // The string table doesn't follow the symbol table, as in linker
+        // outputs that place the hash table or versions between them.
+        let symbols_count = self.symbols.len() as u64 + 1;
+        let symbols = align(header_size + program_headers_count * program_header_size, 8);
+        let hash = align(symbols + symbols_count * symbol_size, 8);
+        let hash_size = match self.hash_table {
+            // A single bucket.
+            HashTable::Sysv => (2 + 1 + symbols_count) * 4,
+            // A single bucket and bloom filter word, hashing every symbol.
+            HashTable::Gnu => 16 + word_size + 4 + (symbols_count - 1) * 4,
+            HashTable::None => 0,
+        };
+        let dynamic_relocations = align(hash + hash_size, 8);
+        let dynamic_relocations_size = self.glob_dats.len() as u64 * relocation_size;
+        let relr = align(dynamic_relocations + dynamic_relocations_size, 8);
+        let relr_size = self.relr.len() as u64 * word_size;
+        let plt_relocations = align(relr + relr_size, 8);
+        let plt_relocations_size = self.jump_slots.len() as u64 * relocation_size;
+        let code = plt_relocations + plt_relocations_size;
+        let strings = code + self.code.len() as u64;
+        let mut string_offsets = Vec::new();
+        let mut string_data = vec![0u8];
+        for symbol in &self.symbols {
+            string_offsets.push(string_data.len() as u64);
+            string_data.extend_from_slice(symbol.name.as_bytes());
+            string_data.push(0);
+        }
+
+        // The writable page: the dynamic segment, the GOT (whose glob-dat
+        // entries are protected by RELRO) and the PLT's GOT.
+        let data = align(strings + string_data.len() as u64, PAGE_SIZE);
+        let got = data + 32 * 2 * word_size;
+        let got_plt = got + self.glob_dats.len() as u64 * word_size;
+        let end = align(
+            got_plt + (3 + self.jump_slots.len() as u64) * word_size,
+            PAGE_SIZE,
+        );
+
+        let (jump_slot_type, glob_dat_type) = self.relocation_types();
+        let (relocation_tag, relocation_size_tag, relocation_entry_tag) = if self.is_rela {
+            (elf::DT_RELA, elf::DT_RELASZ, elf::DT_RELAENT)
+        } else {
+            (elf::DT_REL, elf::DT_RELSZ, elf::DT_RELENT)
+        };
+        let mut dynamic_entries = Vec::new();
+        match self.hash_table {
+            HashTable::Sysv => dynamic_entries.push((elf::DT_HASH, hash)),
+            HashTable::Gnu => dynamic_entries.push((elf::DT_GNU_HASH, hash)),
+            HashTable::None => {}
+        }
+        dynamic_entries.push((elf::DT_SYMTAB, symbols));
+        dynamic_entries.push((elf::DT_SYMENT, symbol_size));
+        dynamic_entries.push((elf::DT_STRTAB, strings));
+        if !self.minimal {
+            dynamic_entries.push((elf::DT_STRSZ, string_data.len() as u64));
+        }
+        dynamic_entries.push((elf::DT_PLTGOT, got_plt));
+        if !self.glob_dats.is_empty() {
+            dynamic_entries.push((relocation_tag, dynamic_relocations));
+            if !self.minimal {
+                dynamic_entries.push((relocation_size_tag, dynamic_relocations_size));
+                dynamic_entries.push((relocation_entry_tag, relocation_size));
+            }
+        }
+        if !self.relr.is_empty() {
+            dynamic_entries.push((36, relr));
+            dynamic_entries.push((35, relr_size));
+            dynamic_entries.push((37, word_size));
+        }
+        if !self.jump_slots.is_empty() {
+            dynamic_entries.push((elf::DT_JMPREL, plt_relocations));
+            if !self.minimal {
+                dynamic_entries.push((elf::DT_PLTRELSZ, plt_relocations_size));
+            }
+            dynamic_entries.push((elf::DT_PLTREL, relocation_tag as u64));
+        }
+        dynamic_entries.push((elf::DT_NULL, 0));
+
+        let mut image = Image {
+            bytes: vec![0; end as usize],
+            is_64: self.is_64,
+            big_endian: self.big_endian,
+        };
+
+        // ELF header.
+        image.bytes[..4].copy_from_slice(&elf::ELFMAG);
+        image.bytes[4] = if self.is_64 {
+            elf::ELFCLASS64
+        } else {
+            elf::ELFCLASS32
+        };
+        image.bytes[5] = if self.big_endian {
+            elf::ELFDATA2MSB
+        } else {
+            elf::ELFDATA2LSB
+        };
+        image.bytes[6] = elf::EV_CURRENT;
+        image.u16(
+            16,
+            if 0 == self.link_address {
+                elf::ET_DYN
+            } else {
+                elf::ET_EXEC
+            },
+        );
+        image.u16(18, self.machine);
+        image.u32(20, elf::EV_CURRENT as u32);
+        let mut offset = 24 + word_size;
+        image.word(offset, header_size);
+        offset += 2 * word_size;
+        image.u32(offset, self.flags);
+        image.u16(offset + 4, header_size as u16);
+        image.u16(offset + 6, program_header_size as u16);
+        image.u16(offset + 8, program_headers_count as u16);
+
+        // Program headers.
+        let mut program_headers = vec![
+            (elf::PT_LOAD, elf::PF_R | elf::PF_X, 0, data),
+            (elf::PT_LOAD, elf::PF_R | elf::PF_W, data, end - data),
+        ];
+        for _ in 0..self.dynamic_segments {
+            program_headers.push((
+                elf::PT_DYNAMIC,
+                elf::PF_R | elf::PF_W,
+                data,
+                dynamic_entries.len() as u64 * 2 * word_size,
+            ));
+        }
+        program_headers.push((elf::PT_GNU_RELRO, elf::PF_R, data, got_plt - data));
+        for (index, &(kind, flags, address, size)) in program_headers.iter().enumerate() {
+            image.program_header(
+                header_size + index as u64 * program_header_size,
+                kind,
+                flags,
+                address,
+                self.link_address + address,
+                size,
+            );
+        }
+
+        // Symbols.
+        for (index, symbol) in self.symbols.iter().enumerate() {
+            image.symbol(
+                symbols + (index as u64 + 1) * symbol_size,
+                string_offsets[index] as u32,
+                symbol.value,
+            );
+        }
+
+        // Hash table.
+        match self.hash_table {
+            HashTable::Sysv => {
+                image.u32(hash, 1);
+                image.u32(hash + 4, symbols_count as u32);
+            }
+            HashTable::Gnu => {
+                image.u32(hash, 1);
+                image.u32(hash + 4, 1);
+                image.u32(hash + 8, 1);
+                image.u32(hash + 12, 0);
+                image.word(hash + 16, u64::MAX);
+                let bucket = hash + 16 + word_size;
+                if !self.symbols.is_empty() {
+                    image.u32(bucket, 1);
+                }
+                for (index, symbol) in self.symbols.iter().enumerate() {
+                    // The chain ends with an odd hash.
+                    let is_last = index + 1 == self.symbols.len();
+                    image.u32(
+                        bucket + 4 + index as u64 * 4,
+                        (gnu_hash(&symbol.name) & !1) | is_last as u32,
+                    );
+                }
+            }
+            HashTable::None => {}
+        }
+
+        // Relocation tables.
+        let mut got_entries = HashMap::new();
+        for (index, &symbol) in self.glob_dats.iter().enumerate() {
+            let got_entry = got + index as u64 * word_size;
+            image.relocation(
+                dynamic_relocations + index as u64 * relocation_size,
+                got_entry,
+                symbol as u64,
+                glob_dat_type,
+            );
+            got_entries.insert(self.symbols[symbol - 1].name.clone(), got_entry);
+        }
+        for (index, &entry) in self.relr.iter().enumerate() {
+            image.word(relr + index as u64 * word_size, entry);
+        }
+        for (index, &symbol) in self.jump_slots.iter().enumerate() {
+            let got_entry = got_plt + (3 + index as u64) * word_size;
+            image.relocation(
+                plt_relocations + index as u64 * relocation_size,
+                got_entry,
+                symbol as u64,
+                jump_slot_type,
+            );
+            got_entries.insert(self.symbols[symbol - 1].name.clone(), got_entry);
+        }
+
+        // Code.
+        image.bytes[code as usize..strings as usize].copy_from_slice(&self.code);
+
+        // Strings.
+        image.bytes[strings as usize..strings as usize + string_data.len()]
+            .copy_from_slice(&string_data);
+
+        // Section headers.
+        if self.sections {
+            let relocation_type = if self.is_rela {
+                elf::SHT_RELA
+            } else {
+                elf::SHT_REL
+            };
+            let (dynamic_relocations_name, plt_relocations_name) = if self.is_rela {
+                (".rela.dyn", ".rela.plt")
+            } else {
+                (".rel.dyn", ".rel.plt")
+            };
+            // Name, type, address, size, link and entry size.
+            let sections = [
+                ("", elf::SHT_NULL, 0, 0, 0, 0),
+                (
+                    ".dynsym",
+                    elf::SHT_DYNSYM,
+                    symbols,
+                    symbols_count * symbol_size,
+                    2,
+                    symbol_size,
+                ),
+                (
+                    ".dynstr",
+                    elf::SHT_STRTAB,
+                    strings,
+                    string_data.len() as u64,
+                    0,
+                    0,
+                ),
+                (
+                    dynamic_relocations_name,
+                    relocation_type,
+                    dynamic_relocations,
+                    dynamic_relocations_size,
+                    1,
+                    relocation_size,
+                ),
+                (
+                    plt_relocations_name,
+                    relocation_type,
+                    plt_relocations,
+                    plt_relocations_size,
+                    1,
+                    relocation_size,
+                ),
+                (".shstrtab", elf::SHT_STRTAB, 0, 0, 0, 0),
+            ];
+
+            let section_names = end;
+            let mut section_name_data = Vec::new();
+            let mut section_name_offsets = Vec::new();
+            for &(name, ..) in &sections {
+                section_name_offsets.push(section_name_data.len() as u32);
+                section_name_data.extend_from_slice(name.as_bytes());
+                section_name_data.push(0);
+            }
+            let section_headers = align(section_names + section_name_data.len() as u64, 8);
+            let section_header_size = if self.is_64 { 64 } else { 40 };
+            image.bytes.resize(
+                (section_headers + sections.len() as u64 * section_header_size) as usize,
+                0,
+            );
+            image.bytes[section_names as usize..section_names as usize + section_name_data.len()]
+                .copy_from_slice(&section_name_data);
+
+            let header_offset = 24 + 2 * word_size;
+            image.word(header_offset, section_headers);
+            image.u16(header_offset + word_size + 10, section_header_size as u16);
+            image.u16(header_offset + word_size + 12, sections.len() as u16);
+            image.u16(header_offset + word_size + 14, sections.len() as u16 - 1);
+
+            for (index, &(_, kind, address, size, link, entry_size)) in sections.iter().enumerate()
+            {
+                let (address, offset, size) = if elf::SHT_STRTAB == kind && 0 == address {
+                    (0, section_names, section_name_data.len() as u64)
+                } else {
+                    (address, address, size)
+                };
+                image.section_header(
+                    section_headers + index as u64 * section_header_size,
+                    section_name_offsets[index],
+                    kind,
+                    address,
+                    offset,
+                    size,
+                    link,
+                    entry_size,
+                );
+            }
+        }
+
+        // Dynamic segment.
+        for (index, &(tag, value)) in dynamic_entries.iter().enumerate() {
+            let entry = data + index as u64 * 2 * word_size;
+            image.word(entry, tag as u64);
+            image.word(entry + word_size, value);
+        }
+
+        TestElf {
+            bytes: image.bytes,
+            code,
+            dynamic_segment: data,
+            dynamic_segment_count: dynamic_entries.len(),
+            got_entries,
+        }
+    }
+}
+
+/// A synthetic ELF image, with the offsets the tests need.
+pub(crate) struct TestElf {
+    pub(crate) bytes: Vec<u8>,
+    #[cfg_attr(not(feature = "got-references"), allow(dead_code))]
+    code: u64,
+    dynamic_segment: u64,
+    dynamic_segment_count: usize,
+    got_entries: HashMap<String, u64>,
+}
+
+impl TestElf {
+    pub(crate) fn dynamic_segment<Elf: FileHeader>(&self) -> &[Elf::Dyn] {
+        self.bytes
+            .as_slice()
+            .read_slice_at(self.dynamic_segment, self.dynamic_segment_count)
+            .unwrap()
+    }
+
+    /// Returns the offset of the GOT entry of the imported `name`.
+    pub(crate) fn got_entry(&self, name: &str) -> u64 {
+        self.got_entries[name]
+    }
+
+    /// Returns the offset of the code placed with [`ElfBuilder::code`].
+    #[cfg_attr(not(feature = "got-references"), allow(dead_code))]
+    pub(crate) fn code(&self) -> u64 {
+        self.code
+    }
+
+    /// Maps the image into anonymous memory, as if a JIT generated it.
+    pub(crate) fn map(&self) -> MappedElf {
+        let address = unsafe {
+            libc::mmap(
+                ptr::null_mut(),
+                self.bytes.len(),
+                libc::PROT_READ | libc::PROT_WRITE,
+                libc::MAP_PRIVATE | libc::MAP_ANONYMOUS,
+                -1,
+                0,
+            )
+        };
+        assert_ne!(libc::MAP_FAILED, address);
+        unsafe {
+            ptr::copy_nonoverlapping(self.bytes.as_ptr(), address as *mut u8, self.bytes.len());
+        }
+
+        self.mapped(address, None)
+    }
+
+    /// Writes the image to a file named `name` in a temporary directory, and
+    /// maps it from there, so that it's listed as a loaded module.
+    pub(crate) fn map_file(&self, name: &str) -> MappedElf {
+        let directory = env::temp_dir().join(format!("gothook-{}", process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        let path = directory.join(name);
+        fs::write(&path, &self.bytes).unwrap();
+
+        let file = File::options().read(true).write(true).open(&path).unwrap();
+        let address = unsafe {
+            libc::mmap(
+                ptr::null_mut(),
+                self.bytes.len(),
+                libc::PROT_READ | libc::PROT_WRITE,
+                libc::MAP_PRIVATE,
+                file.as_raw_fd(),
+                0,
+            )
+        };
+        assert_ne!(libc::MAP_FAILED, address);
+
+        self.mapped(address, Some(path))
+    }
+
+    fn mapped(&self, address: *mut c_void, path: Option<PathBuf>) -> MappedElf {
+        MappedElf {
+            base_address: address as u64,
+            size: self.bytes.len(),
+            path,
+            got_entries: self.got_entries.clone(),
+        }
+    }
+}
+
+/// A synthetic ELF image mapped into the process, unmapped on drop.
+pub(crate) struct MappedElf {
+    base_address: u64,
+    size: usize,
+    path: Option<PathBuf>,
+    got_entries: HashMap<String, u64>,
+}
+
+impl MappedElf {
+    pub(crate) fn base_address(&self) -> u64 {
+        self.base_address
+    }
+
+    pub(crate) fn size(&self) -> u64 {
+        self.size as u64
+    }
+
+    /// Returns the path of the file the image is mapped from.
+    pub(crate) fn path(&self) -> &Path {
+        self.path.as_deref().unwrap()
+    }
+
+    /// Returns the address of the GOT entry of the imported `name`.
+    pub(crate) fn got_entry(&self, name: &str) -> u64 {
+        self.base_address + self.got_entries[name]
+    }
+
+    pub(crate) fn read_got_entry(&self, name: &str) -> u64 {
+        unsafe { ptr::read_volatile(self.got_entry(name) as *const u64) }
+    }
+
+    pub(crate) fn write_got_entry(&self, name: &str, value: u64) {
+        unsafe { ptr::write_volatile(self.got_entry(name) as *mut u64, value) }
+    }
+}
+
+impl Drop for MappedElf {
+    fn drop(&mut self) {
+        unsafe {
+            libc::munmap(self.base_address as *mut c_void, self.size);
+        }
+
+        if let Some(path) = &self.path {
+            let _ = fs::remove_file(path);
+            // Fails while other images are mapped from the directory.
+            let _ = fs::remove_dir(path.parent().unwrap());
+        }
+    }
+}
+
+struct Image {
+    bytes: Vec<u8>,
+    is_64: bool,
+    big_endian: bool,
+}
+
+impl Image {
+    fn put(&mut self, offset: u64, little_endian: &[u8]) {
+        let destination = &mut self.bytes[offset as usize..offset as usize + little_endian.len()];
+        destination.copy_from_slice(little_endian);
+        if self.big_endian {
+            destination.reverse();
+        }
+    }
+
+    fn u16(&mut self, offset: u64, value: u16) {
+        self.put(offset, &value.to_le_bytes());
+    }
+
+    fn u32(&mut self, offset: u64, value: u32) {
+        self.put(offset, &value.to_le_bytes());
+    }
+
+    fn u64(&mut self, offset: u64, value: u64) {
+        self.put(offset, &value.to_le_bytes());
+    }
+
+    fn word(&mut self, offset: u64, value: u64) {
+        if self.is_64 {
+            self.u64(offset, value);
+        } else {
+            self.u32(offset, value as u32);
+        }
+    }
+
+    fn program_header(
+        &mut self,
+        offset: u64,
+        kind: u32,
+        flags: u32,
+        file_offset: u64,
+        address: u64,
+        size: u64,
+    ) {
+        if self.is_64 {
+            self.u32(offset, kind);
+            self.u32(offset + 4, flags);
+            self.u64(offset + 8, file_offset);
+            self.u64(offset + 16, address);
+            self.u64(offset + 24, address);
+            self.u64(offset + 32, size);
+            self.u64(offset + 40, size);
+            self.u64(offset + 48, PAGE_SIZE);
+        } else {
+            self.u32(offset, kind);
+            self.u32(offset + 4, file_offset as u32);
+            self.u32(offset + 8, address as u32);
+            self.u32(offset + 12, address as u32);
+            self.u32(offset + 16, size as u32);
+            self.u32(offset + 20, size as u32);
+            self.u32(offset + 24, flags);
+            self.u32(offset + 28, PAGE_SIZE as u32);
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn section_header(
+        &mut self,
+        offset: u64,
+        name: u32,
+        kind: u32,
+        address: u64,
+        file_offset: u64,
+        size: u64,
+        link: u32,
+        entry_size: u64,
+    ) {
+        let word_size = if self.is_64 { 8 } else { 4 };
+        let flags = if 0 == address {
+            0
+        } else {
+            elf::SHF_ALLOC as u64
+        };
+
+        self.u32(offset, name);
+        self.u32(offset + 4, kind);
+        self.word(offset + 8, flags);
+        self.word(offset + 8 + word_size, address);
+        self.word(offset + 8 + 2 * word_size, file_offset);
+        self.word(offset + 8 + 3 * word_size, size);
+        self.u32(offset + 8 + 4 * word_size, link);
+        self.u32(offset + 12 + 4 * word_size, 0);
+        self.word(offset + 16 + 4 * word_size, word_size);
+        self.word(offset + 16 + 5 * word_size, entry_size);
+    }
+
+    fn symbol(&mut self, offset: u64, name: u32, value: Option<u64>) {
+        let info = (elf::STB_GLOBAL << 4) | elf::STT_FUNC;
+        // Defined symbols are in some section, undefined ones in none.
+        let section = if value.is_some() { 1 } else { elf::SHN_UNDEF };
+
+        self.u32(offset, name);
+        if self.is_64 {
+            self.bytes[offset as usize + 4] = info;
+            self.u16(offset + 6, section);
+            self.u64(offset + 8, value.unwrap_or(0));
+        } else {
+            self.u32(offset + 4, value.unwrap_or(0) as u32);
+            self.bytes[offset as usize + 12] = info;
+            self.u16(offset + 14, section);
+        }
+    }
+
+    // RELA addends are left zeroed.
+    fn relocation(&mut self, offset: u64, address: u64, symbol: u64, kind: u32) {
+        self.word(offset, address);
+        if self.is_64 {
+            self.u64(offset + 8, (symbol << 32) | kind as u64);
+        } else {
+            self.u32(offset + 4, ((symbol << 8) | kind as u64) as u32);
+        }
+    }
+}
+
+fn gnu_hash(name: &str) -> u32 {
+    name.bytes()
+        .fold(5381u32, |h, c| h.wrapping_mul(33).wrapping_add(c as u32))
+}
+
+fn align(value: u64, alignment: u64) -> u64 {
+    (value + alignment - 1) & !(alignment - 1)
+}
@@ -0,0 +1,393 @@
+use std::ffi::{c_int, c_uint, c_void};
+use std::mem;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+use libc::{iovec, loff_t, off_t, size_t, ssize_t};
+use nix::errno::Errno;
+
+use crate::errno;
+use crate::error;
+use crate::libc_functions::{
+    get_original_function, CopyFileRangeFunction, SendFunction, SendfileFunction, WriteFunction,
+};
+use crate::telemetry;
+use crate::GotHook;
+
+const DEFAULT_CAPTURE_LIMIT: usize = 4096;
+
+/// A single call to a traced function.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TraceRecord {
+    pub function_name: &'static str,
+
+    /// The call's arguments, in order.
+    pub arguments: Vec<u64>,
+
+    pub return_value: i64,
+
+    /// A copy of the call's buffer argument, for functions that have one.
+    ///
+    /// At most [`set_trace_capture_limit`] bytes are copied, and nothing is
+    /// copied from an invalid buffer.
+    pub data: Option<Vec<u8>>,
+}
+
+lazy_static::lazy_static! {
+    static ref TRACE_RECORDS: Mutex<Vec<TraceRecord>> = Mutex::new(Vec::new());
+}
+
+static CAPTURE_LIMIT: AtomicUsize = AtomicUsize::new(DEFAULT_CAPTURE_LIMIT);
+
+static ORIGINAL_WRITE: AtomicU64 = AtomicU64::new(0);
+static ORIGINAL_SEND: AtomicU64 = AtomicU64::new(0);
+static ORIGINAL_SENDFILE: AtomicU64 = AtomicU64::new(0);
+static ORIGINAL_COPY_FILE_RANGE: AtomicU64 = AtomicU64::new(0);
+
+// Tracing a traced function again would make its callback its own original
+// function.
+static WRITE_TRACED: AtomicBool = AtomicBool::new(false);
+static SEND_TRACED: AtomicBool = AtomicBool::new(false);
+static SENDFILE_TRACED: AtomicBool = AtomicBool::new(false);
+static COPY_FILE_RANGE_TRACED: AtomicBool = AtomicBool::new(false);
+
+/// Sets the maximal number of bytes copied from a traced call's buffer.
+pub fn set_trace_capture_limit(limit: usize) {
+    CAPTURE_LIMIT.store(limit, Ordering::Relaxed);
+}
+
+/// Returns the calls traced so far, clearing them.
+pub fn take_trace_records() -> Vec<TraceRecord> {
+    mem::take(&mut *TRACE_RECORDS.lock().unwrap())
+}
+
+pub(crate) fn install(function_name: &str) -> error::Result<GotHook> {
+    let (callback, original_function, traced) = match function_name {
+        "write" => (write_callback as *const (), &ORIGINAL_WRITE, &WRITE_TRACED),
+        "send" => (send_callback as *const (), &ORIGINAL_SEND, &SEND_TRACED),
+        "sendfile" => (
+            sendfile_callback as *const (),
+            &ORIGINAL_SENDFILE,
+            &SENDFILE_TRACED,
+        ),
+        "copy_file_range" => (
+            copy_file_range_callback as *const (),
+            &ORIGINAL_COPY_FILE_RANGE,
+            &COPY_FILE_RANGE_TRACED,
+        ),
+        _ => return Err(error::Error::NoTracePrototype(String::from(function_name))),
+    };
+
+    if traced
+        .compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire)
+        .is_err()
+    {
+        return Err(error::Error::CallbackAlreadyInstalled(String::from(
+            function_name,
+        )));
+    }
+
+    let mut hook = match GotHook::new_in_main_executable(function_name, callback) {
+        Ok(hook) => hook,
+        Err(e) => {
+            traced.store(false, Ordering::Release);
+            return Err(e);
+        }
+    };
+    hook.mirror_original_function(original_function);
+    hook.clear_on_restore(traced);
+
+    Ok(hook)
+}
+
+fn record(
+    function_name: &'static str,
+    arguments: Vec<u64>,
+    return_value: i64,
+    data: Option<Vec<u8>>,
+) {
+    // Recording mustn't clobber the errno the caller is about to observe.
+    let errno = Errno::last();
+    telemetry::call_intercepted();
+
+    TRACE_RECORDS.lock().unwrap().push(TraceRecord {
+        function_name,
+        arguments,
+        return_value,
+        data,
+    });
+
+    errno::set_errno(errno);
+}
+
+fn capture_buffer(buffer: *const c_void, length: size_t) -> Option<Vec<u8>> {
+    if buffer.is_null() {
+        return None;
+    }
+
+    // Copy through `process_vm_readv`, which fails with `EFAULT` on an invalid
+    // buffer instead of faulting.
+    let mut data = vec![0u8; length.min(CAPTURE_LIMIT.load(Ordering::Relaxed))];
+    let local = iovec {
+        iov_base: data.as_mut_ptr() as *mut c_void,
+        iov_len: data.len(),
+    };
+    let remote = iovec {
+        iov_base: buffer as *mut c_void,
+        iov_len: data.len(),
+    };
+
+    let copied = unsafe { libc::process_vm_readv(libc::getpid(), &local, 1, &remote, 1, 0) };
+    if copied < 0 {
+        return None;
+    }
+
+    data.truncate(copied as usize);
+    Some(data)
+}
+
+extern "C" fn write_callback(fd: c_int, buf: *const c_void, count: size_t) -> ssize_t {
+    let data = capture_buffer(buf, count);
+
+    let original_write = unsafe {
+        mem::transmute::<*const (), WriteFunction>(get_original_function("write", &ORIGINAL_WRITE))
+    };
+    let result = unsafe { original_write(fd, buf, count) };
+
+    record(
+        "write",
+        vec![fd as u64, buf as u64, count as u64],
+        result as i64,
+        data,
+    );
+    result
+}
+
+extern "C" fn send_callback(
+    sockfd: c_int,
+    buf: *const c_void,
+    len: size_t,
+    flags: c_int,
+) -> ssize_t {
+    let data = capture_buffer(buf, len);
+
+    let original_send = unsafe {
+        mem::transmute::<*const (), SendFunction>(get_original_function("send", &ORIGINAL_SEND))
+    };
+    let result = unsafe { original_send(sockfd, buf, len, flags) };
+
+    record(
+        "send",
+        vec![sockfd as u64, buf as u64, len as u64, flags as u64],
+        result as i64,
+        data,
+    );
+    result
+}
+
+extern "C" fn sendfile_callback(
+    out_fd: c_int,
+    in_fd: c_int,
+    offset: *mut off_t,
+    count: size_t,
+) -> ssize_t {
+    let original_sendfile = unsafe {
+        mem::transmute::<*const (), SendfileFunction>(get_original_function(
+            "sendfile",
+            &ORIGINAL_SENDFILE,
+        ))
+    };
+    let result = unsafe { original_sendfile(out_fd, in_fd, offset, count) };
+
+    // The data never passes through our address space, so there's no buffer
+    // to capture.
+    record(
+        "sendfile",
+        vec![out_fd as u64, in_fd as u64, offset as u64, count as u64],
+        result as i64,
+        None,
+    );
+    result
+}
+
+extern "C" fn copy_file_range_callback(
+    fd_in: c_int,
+    off_in: *mut loff_t,
+    fd_out: c_int,
+    off_out: *mut loff_t,
+    len: size_t,
+    flags: c_uint,
+) -> ssize_t {
+    let original_copy_file_range = unsafe {
+        mem::transmute::<*const (), CopyFileRangeFunction>(get_original_function(
+            "copy_file_range",
+            &ORIGINAL_COPY_FILE_RANGE,
+        ))
+    };
+    let result = unsafe { original_copy_file_range(fd_in, off_in, fd_out, off_out, len, flags) };
+
+    record(
+        "copy_file_range",
+        vec![
+            fd_in as u64,
+            off_in as u64,
+            fd_out as u64,
+            off_out as u64,
+            len as u64,
+            flags as u64,
+        ],
+        result as i64,
+        None,
+    );
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs::{self, File};
+    use std::os::fd::AsRawFd;
+    use std::process;
+    use std::ptr;
+    use std::sync::MutexGuard;
+
+    use super::*;
+
+    // The trace records and the capture limit are global.
+    static TRACE_LOCK: Mutex<()> = Mutex::new(());
+
+    fn lock() -> MutexGuard<'static, ()> {
+        let lock = TRACE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        set_trace_capture_limit(DEFAULT_CAPTURE_LIMIT);
+        take_trace_records();
+        lock
+    }
+
+    fn pipe() -> (c_int, c_int) {
+        let mut fds = [0; 2];
+        assert_eq!(0, unsafe { libc::pipe(fds.as_mut_ptr()) });
+        (fds[0], fds[1])
+    }
+
+    #[test]
+    fn captures_written_bytes() {
+        let _lock = lock();
+        let (read_fd, write_fd) = pipe();
+
+        let buffer = b"hello, world";
+        let result = write_callback(write_fd, buffer.as_ptr() as *const c_void, buffer.len());
+        assert_eq!(buffer.len() as ssize_t, result);
+
+        // Bounded by the capture limit.
+        set_trace_capture_limit(5);
+        write_callback(write_fd, buffer.as_ptr() as *const c_void, buffer.len());
+
+        assert_eq!(
+            take_trace_records(),
+            [
+                TraceRecord {
+                    function_name: "write",
+                    arguments: vec![write_fd as u64, buffer.as_ptr() as u64, buffer.len() as u64],
+                    return_value: buffer.len() as i64,
+                    data: Some(buffer.to_vec()),
+                },
+                TraceRecord {
+                    function_name: "write",
+                    arguments: vec![write_fd as u64, buffer.as_ptr() as u64, buffer.len() as u64],
+                    return_value: buffer.len() as i64,
+                    data: Some(b"hello".to_vec()),
+                },
+            ]
+        );
+
+        unsafe {
+            libc::close(read_fd);
+            libc::close(write_fd);
+        }
+    }
+
+    #[test]
+    fn doesnt_capture_null_or_invalid_buffers() {
+        let _lock = lock();
+        let (read_fd, write_fd) = pipe();
+
+        // An inaccessible page.
+        let invalid_buffer = unsafe {
+            libc::mmap(
+                ptr::null_mut(),
+                4096,
+                libc::PROT_NONE,
+                libc::MAP_PRIVATE | libc::MAP_ANONYMOUS,
+                -1,
+                0,
+            )
+        };
+        assert_ne!(libc::MAP_FAILED, invalid_buffer);
+
+        for buffer in [ptr::null(), invalid_buffer as *const c_void] {
+            assert_eq!(-1, write_callback(write_fd, buffer, 16));
+            assert_eq!(Errno::EFAULT, Errno::last());
+        }
+
+        let records = take_trace_records();
+        assert_eq!(2, records.len());
+        for record in records {
+            assert_eq!(-1, record.return_value);
+            assert_eq!(None, record.data);
+        }
+
+        unsafe {
+            libc::munmap(invalid_buffer, 4096);
+            libc::close(read_fd);
+            libc::close(write_fd);
+        }
+    }
+
+    #[test]
+    fn records_the_bytes_sendfile_transferred() {
+        let _lock = lock();
+        let (read_fd, write_fd) = pipe();
+
+        let contents = b"sent without passing through a buffer";
+        let path = env::temp_dir().join(format!("gothook-sendfile-{}", process::id()));
+        fs::write(&path, contents).unwrap();
+        let file = File::open(&path).unwrap();
+
+        // Ask for more than the file holds.
+        let result = sendfile_callback(write_fd, file.as_raw_fd(), ptr::null_mut(), 4096);
+        assert_eq!(contents.len() as ssize_t, result);
+
+        let mut transferred = vec![0u8; 4096];
+        let read = unsafe { libc::read(read_fd, transferred.as_mut_ptr() as *mut c_void, 4096) };
+        assert_eq!(result, read);
+        assert_eq!(contents, &transferred[..read as usize]);
+
+        assert_eq!(
+            take_trace_records(),
+            [TraceRecord {
+                function_name: "sendfile",
+                arguments: vec![write_fd as u64, file.as_raw_fd() as u64, 0, 4096],
+                return_value: contents.len() as i64,
+                data: None,
+            }]
+        );
+
+        fs::remove_file(&path).unwrap();
+        unsafe {
+            libc::close(read_fd);
+            libc::close(write_fd);
+        }
+    }
+
+    #[test]
+    fn refuses_tracing_traced_functions() {
+        let _lock = lock();
+
+        COPY_FILE_RANGE_TRACED.store(true, Ordering::Release);
+        assert!(matches!(
+            install("copy_file_range"),
+            Err(error::Error::CallbackAlreadyInstalled(f)) if f == "copy_file_range"
+        ));
+        COPY_FILE_RANGE_TRACED.store(false, Ordering::Release);
+    }
+}
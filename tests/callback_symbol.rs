@@ -0,0 +1,56 @@
+mod common;
+
+use gothook::{error, GotHook};
+
+const HANDLER_SOURCE: &str = r#"
+int gothook_test_getpid(void) {
+    return 4242;
+}
+"#;
+
+#[test]
+#[cfg_attr(
+    not(any(
+        target_arch = "aarch64",
+        target_arch = "powerpc64",
+        target_arch = "s390x"
+    )),
+    ignore
+)]
+fn hooks_with_a_callback_symbol_from_another_library() {
+    let library = common::compile_shared_library("callback_symbol", HANDLER_SOURCE);
+
+    let hook =
+        GotHook::new_callback_symbol("getpid", library.to_str().unwrap(), "gothook_test_getpid")
+            .unwrap();
+    assert_eq!(4242, unsafe { libc::getpid() });
+
+    // The callback was resolved in the handler library.
+    let callback_info = hook.callback_dl_info().unwrap();
+    assert_eq!(library.to_str().unwrap(), callback_info.module_name);
+    assert_eq!(
+        Some("gothook_test_getpid"),
+        callback_info.symbol_name.as_deref()
+    );
+
+    hook.unhook().unwrap();
+    assert_ne!(4242, unsafe { libc::getpid() });
+}
+
+#[test]
+fn reports_missing_callback_symbols() {
+    let library = common::compile_shared_library("callback_symbol_missing", HANDLER_SOURCE);
+
+    assert!(matches!(
+        GotHook::new_callback_symbol("getpid", library.to_str().unwrap(), "gothook_test_missing"),
+        Err(error::Error::FindModuleSymbol(_, symbol, _)) if symbol == "gothook_test_missing"
+    ));
+}
+
+#[test]
+fn reports_missing_callback_modules() {
+    assert!(matches!(
+        GotHook::new_callback_symbol("getpid", "libgothook_test_missing.so", "handler"),
+        Err(error::Error::LoadModule(module, _)) if module == "libgothook_test_missing.so"
+    ));
+}
@@ -0,0 +1,36 @@
+// Helpers shared by the integration tests.
+
+#![allow(dead_code)]
+
+use std::path::PathBuf;
+use std::process::Command;
+
+/// Compiles the C `source` into a shared library named `lib<name>.so`.
+pub fn compile_shared_library(name: &str, source: &str) -> PathBuf {
+    compile_shared_library_with(name, source, &[])
+}
+
+/// Compiles the C `source` into a shared library named `lib<name>.so`,
+/// passing `flags` to the compiler.
+pub fn compile_shared_library_with(name: &str, source: &str, flags: &[&str]) -> PathBuf {
+    let directory = PathBuf::from(env!("CARGO_TARGET_TMPDIR"));
+    let source_path = directory.join(format!("{name}.c"));
+    let library_path = directory.join(format!("lib{name}.so"));
+    std::fs::write(&source_path, source).unwrap();
+
+    let status = Command::new("cc")
+        .args(["-shared", "-fPIC", "-O1"])
+        .args(flags)
+        .arg("-o")
+        .arg(&library_path)
+        .arg(&source_path)
+        .status()
+        .unwrap();
+    assert!(
+        status.success(),
+        "failed compiling {}",
+        source_path.display()
+    );
+
+    library_path
+}
@@ -0,0 +1,42 @@
+mod common;
+
+use gothook::{error, HookSet, HookSpec};
+
+const HANDLER_SOURCE: &str = r#"
+int gothook_test_getpid(void) {
+    return 4242;
+}
+
+int gothook_test_getppid(void) {
+    return 4343;
+}
+"#;
+
+#[test]
+#[cfg_attr(
+    not(any(
+        target_arch = "aarch64",
+        target_arch = "powerpc64",
+        target_arch = "s390x"
+    )),
+    ignore
+)]
+fn rolls_back_when_the_third_hook_fails() {
+    let library = common::compile_shared_library("hook_set", HANDLER_SOURCE);
+    let library = library.to_str().unwrap();
+    let pid = unsafe { libc::getpid() };
+    let parent_pid = unsafe { libc::getppid() };
+
+    let result = HookSet::install_all_or_nothing(&[
+        HookSpec::new("getpid", library, "gothook_test_getpid"),
+        HookSpec::new("getppid", library, "gothook_test_getppid"),
+        HookSpec::new("getuid", library, "gothook_test_getuid"),
+    ]);
+
+    assert!(matches!(
+        result,
+        Err(error::Error::FindModuleSymbol(_, symbol, _)) if symbol == "gothook_test_getuid"
+    ));
+    assert_eq!(pid, unsafe { libc::getpid() });
+    assert_eq!(parent_pid, unsafe { libc::getppid() });
+}
@@ -0,0 +1,46 @@
+use std::ffi::c_void;
+
+use gothook::{analyze, loaded_modules, ModuleInfo};
+
+fn module_containing(modules: &[ModuleInfo], address: u64) -> &ModuleInfo {
+    modules
+        .iter()
+        .find(|m| (m.base_address..m.base_address + m.size).contains(&address))
+        .unwrap_or_else(|| panic!("no module contains [{address:x}]"))
+}
+
+#[test]
+fn lists_the_main_executable_and_libc() {
+    let modules = loaded_modules().unwrap();
+
+    // Modules are page-aligned, in address order, and don't overlap.
+    for module in &modules {
+        assert_eq!(0, module.base_address % 4096, "{module:?}");
+        assert_ne!(0, module.size, "{module:?}");
+    }
+    for pair in modules.windows(2) {
+        assert!(
+            pair[0].base_address + pair[0].size <= pair[1].base_address,
+            "{pair:?}"
+        );
+    }
+
+    // The main executable is the test binary, and contains its code.
+    let main_executable_base = analyze::main_executable_base().unwrap();
+    let main_executable = module_containing(
+        &modules,
+        lists_the_main_executable_and_libc as *const () as u64,
+    );
+    assert_eq!(main_executable_base, main_executable.base_address);
+    assert_eq!(
+        std::env::current_exe().unwrap().canonicalize().unwrap(),
+        main_executable.path.canonicalize().unwrap()
+    );
+
+    // libc contains `getpid`.
+    let getpid = unsafe { libc::dlsym(libc::RTLD_DEFAULT, c"getpid".as_ptr()) };
+    assert_ne!(std::ptr::null_mut::<c_void>(), getpid);
+    let libc = module_containing(&modules, getpid as u64);
+    let libc_name = libc.path.file_name().unwrap().to_string_lossy();
+    assert!(libc_name.starts_with("libc"), "{libc:?}");
+}
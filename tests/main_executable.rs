@@ -0,0 +1,52 @@
+mod common;
+
+use std::ffi::CString;
+use std::mem;
+
+use gothook::GotHook;
+
+const LIBRARY_SOURCE: &str = r#"
+#include <unistd.h>
+
+pid_t gothook_test_library_getpid(void) {
+    return getpid();
+}
+"#;
+
+extern "C" fn getpid_callback() -> libc::pid_t {
+    4242
+}
+
+#[test]
+#[cfg_attr(
+    not(any(
+        target_arch = "aarch64",
+        target_arch = "powerpc64",
+        target_arch = "s390x"
+    )),
+    ignore
+)]
+fn leaves_library_calls_unhooked() {
+    let library = common::compile_shared_library("main_executable", LIBRARY_SOURCE);
+    let library_path = CString::new(library.to_str().unwrap()).unwrap();
+    let handle = unsafe { libc::dlopen(library_path.as_ptr(), libc::RTLD_NOW) };
+    assert!(!handle.is_null());
+    let library_getpid = unsafe {
+        mem::transmute::<*mut libc::c_void, extern "C" fn() -> libc::pid_t>(libc::dlsym(
+            handle,
+            c"gothook_test_library_getpid".as_ptr(),
+        ))
+    };
+
+    // Captured before hooking, `process::id` calls `getpid` too.
+    let pid = std::process::id() as libc::pid_t;
+
+    let hook = GotHook::new_in_main_executable("getpid", getpid_callback as *const ()).unwrap();
+    assert_eq!(4242, unsafe { libc::getpid() });
+
+    // The library calls `getpid` through its own GOT.
+    assert_eq!(pid, library_getpid());
+
+    hook.unhook().unwrap();
+    assert_eq!(pid, unsafe { libc::getpid() });
+}
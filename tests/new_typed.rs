@@ -0,0 +1,27 @@
+use gothook::GotHook;
+
+extern "C" fn getpid_callback() -> libc::pid_t {
+    4242
+}
+
+#[test]
+#[cfg_attr(
+    not(any(
+        target_arch = "aarch64",
+        target_arch = "powerpc64",
+        target_arch = "s390x"
+    )),
+    ignore
+)]
+fn calls_the_original_function_through_the_callbacks_type() {
+    // Captured before hooking, `process::id` calls `getpid` too.
+    let pid = std::process::id() as libc::pid_t;
+
+    let (hook, original_getpid) =
+        GotHook::new_typed("getpid", getpid_callback as extern "C" fn() -> libc::pid_t).unwrap();
+    assert_eq!(4242, unsafe { libc::getpid() });
+    assert_eq!(pid, original_getpid());
+
+    hook.unhook().unwrap();
+    assert_eq!(pid, unsafe { libc::getpid() });
+}
@@ -0,0 +1,89 @@
+mod common;
+
+use std::ffi::CString;
+use std::mem;
+
+use gothook::ModuleHooker;
+
+const LAZY_SOURCE: &str = r#"
+#include <unistd.h>
+
+pid_t gothook_test_lazy_getppid(void) {
+    return getppid();
+}
+"#;
+
+extern "C" fn getppid_callback() -> libc::pid_t {
+    4242
+}
+
+/// Calls the unresolved `getppid` the way the library's PLT stub would.
+unsafe fn call_original(original: *const (), got_entry: u64) -> libc::pid_t {
+    #[cfg(target_arch = "aarch64")]
+    {
+        // The resolution stub finds the relocation through the GOT entry in x16.
+        let pid: libc::c_long;
+        unsafe {
+            std::arch::asm!(
+                "blr {original}",
+                original = in(reg) original,
+                in("x16") got_entry,
+                lateout("x0") pid,
+                clobber_abi("C"),
+            );
+        }
+        pid as libc::pid_t
+    }
+
+    #[cfg(not(target_arch = "aarch64"))]
+    {
+        let _ = got_entry;
+        unsafe { mem::transmute::<*const (), extern "C" fn() -> libc::pid_t>(original)() }
+    }
+}
+
+#[test]
+#[cfg_attr(
+    not(any(
+        target_arch = "aarch64",
+        target_arch = "powerpc64",
+        target_arch = "s390x"
+    )),
+    ignore
+)]
+fn primes_lazily_bound_functions() {
+    let library =
+        common::compile_shared_library_with("prime_original", LAZY_SOURCE, &["-Wl,-z,lazy"]);
+    let library = library.to_str().unwrap();
+    let parent_pid = unsafe { libc::getppid() };
+
+    // Load the library without resolving its functions.
+    let library_path = CString::new(library).unwrap();
+    let handle = unsafe { libc::dlopen(library_path.as_ptr(), libc::RTLD_LAZY) };
+    assert!(!handle.is_null());
+    let lazy_getppid = unsafe {
+        mem::transmute::<*mut libc::c_void, extern "C" fn() -> libc::pid_t>(libc::dlsym(
+            handle,
+            c"gothook_test_lazy_getppid".as_ptr(),
+        ))
+    };
+
+    let mut hook = ModuleHooker::new(library)
+        .unwrap()
+        .hook("getppid", getppid_callback as *const ())
+        .unwrap();
+    let got_entry = hook.state().got_entry;
+    hook.prime_original(|original| {
+        assert_eq!(parent_pid, unsafe { call_original(original, got_entry) });
+    })
+    .unwrap();
+
+    // The resolved function replaced the resolution stub, and the hook was
+    // kept installed.
+    let getppid = unsafe { libc::dlsym(libc::RTLD_DEFAULT, c"getppid".as_ptr()) };
+    assert_eq!(getppid as *const (), hook.get_original_function());
+    assert_eq!(4242, lazy_getppid());
+
+    hook.unhook().unwrap();
+    assert_eq!(parent_pid, lazy_getppid());
+}
@@ -0,0 +1,90 @@
+mod common;
+
+use std::env;
+use std::process::Command;
+
+use gothook::HookSpec;
+
+// Set in the re-exec'd test binary, to the check `reexec_child` performs.
+const CHILD_ENVIRONMENT_VARIABLE: &str = "GOTHOOK_TEST_REEXEC_CHILD";
+const LIBRARY_ENVIRONMENT_VARIABLE: &str = "GOTHOOK_TEST_REEXEC_LIBRARY";
+
+const HANDLER_SOURCE: &str = r#"
+int gothook_test_getpid(void) {
+    return 4242;
+}
+
+int gothook_test_getppid(void) {
+    return 4343;
+}
+"#;
+
+fn specs(library: &str) -> [HookSpec; 2] {
+    [
+        HookSpec::new("getpid", library, "gothook_test_getpid"),
+        HookSpec::new("getppid", library, "gothook_test_getppid"),
+    ]
+}
+
+fn reexec(check: &str, library: &str, (name, value): (&str, String)) {
+    let output = Command::new(env::current_exe().unwrap())
+        .args(["--exact", "reexec_child"])
+        .env(CHILD_ENVIRONMENT_VARIABLE, check)
+        .env(LIBRARY_ENVIRONMENT_VARIABLE, library)
+        .env(name, value)
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "{stdout}");
+    assert!(stdout.contains("1 passed"), "{stdout}");
+}
+
+#[test]
+fn reexec_child() {
+    let Ok(check) = env::var(CHILD_ENVIRONMENT_VARIABLE) else {
+        // Not re-exec'd.
+        return;
+    };
+    let library = env::var(LIBRARY_ENVIRONMENT_VARIABLE).unwrap();
+    let specs = specs(&library);
+
+    match check.as_str() {
+        "select" => assert_eq!(HookSpec::from_env(&specs), [specs[1].clone()]),
+        "reinstall" => {
+            let hooks = HookSpec::reinstall_from_env(&specs).unwrap();
+            assert_eq!(1, hooks.hooks().len());
+            assert_eq!(4343, unsafe { libc::getppid() });
+            assert_ne!(4242, unsafe { libc::getpid() });
+        }
+        _ => unreachable!(),
+    }
+}
+
+#[test]
+fn passes_hook_specs_to_reexecd_processes() {
+    let library = String::from("libgothook_test_unused.so");
+
+    // Unknown function names don't select anything.
+    let (name, value) = HookSpec::env_var(&specs(&library)[1..]);
+    reexec("select", &library, (name, format!("open,{value},execve")));
+}
+
+#[test]
+#[cfg_attr(
+    not(any(
+        target_arch = "aarch64",
+        target_arch = "powerpc64",
+        target_arch = "s390x"
+    )),
+    ignore
+)]
+fn reinstalls_hooks_in_reexecd_processes() {
+    let library = common::compile_shared_library("reexec", HANDLER_SOURCE);
+    let library = library.to_str().unwrap();
+
+    reexec(
+        "reinstall",
+        library,
+        HookSpec::env_var(&specs(library)[1..]),
+    );
+}
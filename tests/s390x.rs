@@ -0,0 +1,24 @@
+use std::ffi::c_int;
+
+use gothook::GotHook;
+
+extern "C" fn getpid_callback() -> c_int {
+    4242
+}
+
+#[test]
+#[cfg_attr(not(target_arch = "s390x"), ignore)]
+fn hooks_the_test_binary_on_s390x() {
+    // Hooking also affects `std`'s own calls to `getpid`.
+    let pid = std::process::id() as c_int;
+
+    let hook = GotHook::new("getpid", getpid_callback as *const ()).unwrap();
+    assert_eq!(4242, unsafe { libc::getpid() });
+
+    let original: extern "C" fn() -> c_int =
+        unsafe { std::mem::transmute(hook.get_original_function()) };
+    assert_eq!(pid, original());
+
+    hook.unhook().unwrap();
+    assert_eq!(pid, unsafe { libc::getpid() });
+}
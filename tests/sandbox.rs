@@ -0,0 +1,57 @@
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+use gothook::{sandbox_filesystem, FsAccess, FsAction, FsPolicy};
+
+#[test]
+#[cfg_attr(
+    not(any(
+        target_arch = "aarch64",
+        target_arch = "powerpc64",
+        target_arch = "s390x"
+    )),
+    ignore
+)]
+fn denies_writes_outside_a_directory_and_redirects_a_read() {
+    let directory = PathBuf::from(env!("CARGO_TARGET_TMPDIR"))
+        .canonicalize()
+        .unwrap()
+        .join("sandbox");
+    fs::create_dir_all(&directory).unwrap();
+    fs::write(directory.join("secret"), "secret").unwrap();
+    fs::write(directory.join("decoy"), "decoy").unwrap();
+
+    let sandbox = sandbox_filesystem(
+        FsPolicy::new(FsAction::Allow)
+            .rule(&directory, FsAccess::Write, FsAction::Allow)
+            .rule("/", FsAccess::Write, FsAction::Deny)
+            .rule(
+                directory.join("secret"),
+                FsAccess::Read,
+                FsAction::Redirect(directory.join("decoy")),
+            ),
+    )
+    .unwrap();
+
+    fs::write(directory.join("inside"), "inside").unwrap();
+    for outside in [
+        directory.with_file_name("sandbox-escape"),
+        directory.join("../sandbox-escape"),
+    ] {
+        assert_eq!(
+            io::ErrorKind::PermissionDenied,
+            fs::write(&outside, "outside").unwrap_err().kind()
+        );
+    }
+    assert_eq!(
+        "decoy",
+        fs::read_to_string(directory.join("secret")).unwrap()
+    );
+
+    drop(sandbox);
+    assert_eq!(
+        "secret",
+        fs::read_to_string(directory.join("secret")).unwrap()
+    );
+}
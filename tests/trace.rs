@@ -0,0 +1,51 @@
+use std::ffi::{c_int, c_void};
+
+use gothook::{error, take_trace_records, GotHook, TraceRecord};
+
+fn pipe() -> (c_int, c_int) {
+    let mut fds = [0; 2];
+    assert_eq!(0, unsafe { libc::pipe(fds.as_mut_ptr()) });
+    (fds[0], fds[1])
+}
+
+fn records_of(fd: c_int) -> Vec<TraceRecord> {
+    take_trace_records()
+        .into_iter()
+        .filter(|r| Some(&(fd as u64)) == r.arguments.first())
+        .collect()
+}
+
+#[test]
+#[cfg_attr(
+    not(any(
+        target_arch = "aarch64",
+        target_arch = "powerpc64",
+        target_arch = "s390x"
+    )),
+    ignore
+)]
+fn captures_the_bytes_of_traced_writes() {
+    let (read_fd, write_fd) = pipe();
+
+    let hook = GotHook::trace("write").unwrap();
+    assert!(matches!(
+        GotHook::trace("write"),
+        Err(error::Error::CallbackAlreadyInstalled(f)) if f == "write"
+    ));
+    let buffer = b"traced";
+    let result = unsafe { libc::write(write_fd, buffer.as_ptr() as *const c_void, buffer.len()) };
+    hook.unhook().unwrap();
+
+    // The function can be traced again once its hook is removed.
+    GotHook::trace("write").unwrap().unhook().unwrap();
+
+    assert_eq!(buffer.len() as isize, result);
+    let records = records_of(write_fd);
+    assert_eq!(1, records.len());
+    assert_eq!(Some(buffer.to_vec()), records[0].data);
+
+    unsafe {
+        libc::close(read_fd);
+        libc::close(write_fd);
+    }
+}